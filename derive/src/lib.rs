@@ -0,0 +1,2715 @@
+//! A single-derive macro merging Clap + config, defaulting field names to kebab-case.
+//! Now supports bool fields with or without default_value, avoiding parse errors.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, quote_spanned};
+use syn::{parse_macro_input, DeriveInput, Error, LitStr};
+
+mod parse_attrs;
+use heck::{ToKebabCase, ToShoutySnakeCase, ToSnakeCase};
+use parse_attrs::*;
+
+#[proc_macro_derive(
+    ClapConfigFile,
+    attributes(
+        config_file_name,
+        config_file_formats,
+        config_file_format_precedence,
+        config_file_merge,
+        config_file_cargo_metadata,
+        config_file_nonempty,
+        config_file_cli_file_mode,
+        config_file_env_ignore_empty,
+        config_file_recursive,
+        config_file_wrap_key,
+        config_file_stop_at,
+        config_file_include,
+        config_on_error,
+        config_case,
+        config_deny_unknown,
+        config_file_search,
+        config_file_examples,
+        config_file_require_explicit,
+        config_from_str,
+        config_hash_eq,
+        config_getters,
+        config_validate,
+        config_auto_short,
+        config_serialize_internal,
+        config_arg
+    )
+)]
+pub fn derive_clap_config_file(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    match build_impl(ast) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn build_impl(ast: DeriveInput) -> syn::Result<TokenStream2> {
+    let struct_ident = &ast.ident;
+    let generics = &ast.generics;
+
+    let macro_cfg = parse_struct_level_attrs(&ast.attrs)?;
+    // The struct's doc comment describes the command in its man page
+    let (about, long_about) = gather_doc_comments(&ast.attrs);
+    let man_about = (!about.is_empty()).then(|| {
+        let long_about = long_about.map(|text| quote!(.long_about(#text)));
+        quote! {
+            if cmd.get_about().is_none() {
+                cmd = cmd.about(#about) #long_about;
+            }
+        }
+    });
+
+    let mut field_infos = match &ast.data {
+        syn::Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Named(ref named),
+            ..
+        }) => parse_fields(&named.named, None)?,
+        syn::Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Unnamed(ref unnamed),
+            ..
+        }) if unnamed.unnamed.len() == 1 => {
+            // `struct Port(u16)` => `--port` / `port:`
+            let name =
+                syn::Ident::new(&struct_ident.to_string().to_snake_case(), Span::call_site());
+            parse_fields(&unnamed.unnamed, Some(name))?
+        }
+        syn::Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Unnamed(_),
+            ..
+        }) => {
+            return Err(Error::new_spanned(
+                &ast.ident,
+                "ClapConfigFile supports tuple structs only with exactly one field (newtypes).",
+            ))
+        }
+        _ => {
+            return Err(Error::new_spanned(
+                &ast.ident,
+                "ClapConfigFile only supports a struct with named fields.",
+            ))
+        }
+    };
+    if macro_cfg.getters {
+        validate_getter_names(&field_infos)?;
+    }
+    if macro_cfg.kebab_keys {
+        for f in &mut field_infos {
+            f.arg_attrs.kebab_key = true;
+        }
+    }
+    if macro_cfg.include {
+        if let Some(f) = field_infos.iter().find(|f| {
+            f.config_key() == "include" || f.config_key_aliases().iter().any(|a| a == "include")
+        }) {
+            return Err(Error::new(
+                f.ident.span(),
+                "config_file_include reserves the `include` config key; rename this field's key with config_arg(name = \"...\")",
+            ));
+        }
+    }
+    // Proc macros can't warn on stable; using a deprecated item makes rustc do it
+    let auto_short_warning = match macro_cfg.auto_short {
+        true => {
+            let left_out = assign_auto_shorts(&mut field_infos);
+            (!left_out.is_empty()).then(|| {
+                let note = format!(
+                    "config_auto_short: no short flag left for --{}",
+                    left_out.join(", --")
+                );
+                quote_spanned! {struct_ident.span()=>
+                    const _: () = {
+                        #[deprecated(note = #note)]
+                        #[allow(non_upper_case_globals)]
+                        const config_auto_short: () = ();
+                        config_auto_short
+                    };
+                }
+            })
+        }
+        false => None,
+    };
+    let cli_struct = generate_cli_struct(struct_ident, &field_infos, &macro_cfg);
+    let cli_ident = cli_struct_ident(struct_ident);
+    let parse_info_impl = generate_parse_info_impl(struct_ident, &field_infos, &macro_cfg);
+
+    let debug_impl = generate_debug_impl(struct_ident, generics, &field_infos);
+    let serialize_impl = generate_serialize_impl(
+        struct_ident,
+        generics,
+        &field_infos,
+        macro_cfg.serialize_internal,
+    );
+    let from_str_impl = if macro_cfg.from_str {
+        generate_from_str_impl(struct_ident, generics, &field_infos)?
+    } else {
+        quote!()
+    };
+
+    let fmts = &macro_cfg.formats;
+    let schema_entries = generate_schema_entries(&field_infos);
+    let template_entries = generate_template_entries(&field_infos, |f| {
+        let member = &f.member;
+        quote!(self.#member)
+    });
+    let saved_fields: Vec<FieldInfo> = field_infos
+        .iter()
+        .filter(|f| !f.arg_attrs.sensitive)
+        .cloned()
+        .collect();
+    let saved_entries = generate_template_entries(&saved_fields, |f| {
+        let member = &f.member;
+        quote!(self.#member)
+    });
+    let sensitive_names: Vec<String> = field_infos
+        .iter()
+        .filter(|f| f.arg_attrs.sensitive)
+        .map(|f| f.ident.to_string())
+        .collect();
+    let to_args_body = generate_to_args_body(&field_infos);
+    let diff_entries = generate_diff_entries(&field_infos);
+
+    let hash_eq_impl = if macro_cfg.hash_eq {
+        generate_hash_eq_impl(struct_ident, generics, &field_infos)
+    } else {
+        quote!()
+    };
+    // `#[config_validate]`: only the `parse` family enforces it; `parse_info` stays as merged
+    let validate = macro_cfg.validate.then(|| {
+        quote! {
+            ::clap_config_file::ValidateConfig::validate(&cfg)
+                .map_err(::clap_config_file::ClapConfigError::Invalid)?;
+        }
+    });
+    let getters_impl = if macro_cfg.getters {
+        generate_getters_impl(struct_ident, generics, &field_infos)
+    } else {
+        quote!()
+    };
+
+    let expanded = quote! {
+        #cli_struct
+
+
+        impl #generics #struct_ident #generics {
+            pub fn parse_info() -> (Self, Option<std::path::PathBuf>, Option<&'static str>) {
+                Self::parse_info_with_fs(std::env::args_os(), &::clap_config_file::RealFs)
+            }
+            /// Like `parse_info`, but with explicit CLI args and all config file
+            /// discovery/reading going through `fs` (e.g. a `MemoryFs`).
+            pub fn parse_info_with_fs<I, T>(
+                args: I,
+                fs: &dyn ::clap_config_file::ConfigFs,
+            ) -> (Self, Option<std::path::PathBuf>, Option<&'static str>)
+            where
+                I: IntoIterator<Item = T>,
+                T: Into<std::ffi::OsString> + Clone,
+            {
+                Self::parse_info_with_fs_and_sources(args, fs, Vec::new())
+            }
+            /// Like `parse_info`, with `extra` custom sources (Consul, Vault, ...) layered
+            /// over config files, in order. Inline `--config`, env and CLI still win.
+            pub fn parse_info_with_sources(
+                extra: Vec<Box<dyn ::config::Source + Send + Sync>>,
+            ) -> (Self, Option<std::path::PathBuf>, Option<&'static str>) {
+                Self::parse_info_with_fs_and_sources(
+                    std::env::args_os(),
+                    &::clap_config_file::RealFs,
+                    extra,
+                )
+            }
+            /// Like `parse_info`, with explicit CLI args and an optional config string
+            /// (any declared format) layered over the config files, as a custom source
+            /// is. Handy for tests and embedding: no env vars or files to set up.
+            pub fn parse_info_from<I, T>(
+                args: I,
+                config_override: Option<&str>,
+            ) -> (Self, Option<std::path::PathBuf>, Option<&'static str>)
+            where
+                I: IntoIterator<Item = T>,
+                T: Into<std::ffi::OsString> + Clone,
+            {
+                Self::try_parse_info_from(args, config_override).unwrap_or_else(|e| e.exit())
+            }
+            /// Fallible `parse_info_from`: a `config_override` in none of the declared
+            /// formats is `ClapConfigError::Invalid`.
+            pub fn try_parse_info_from<I, T>(
+                args: I,
+                config_override: Option<&str>,
+            ) -> Result<
+                (Self, Option<std::path::PathBuf>, Option<&'static str>),
+                ::clap_config_file::ClapConfigError,
+            >
+            where
+                I: IntoIterator<Item = T>,
+                T: Into<std::ffi::OsString> + Clone,
+            {
+                let mut extra: Vec<Box<dyn ::config::Source + Send + Sync>> = Vec::new();
+                if let Some(raw) = config_override {
+                    let (_, file_format) = ::clap_config_file::format::detect_format(raw, &[#(#fmts),*])
+                        .ok_or_else(|| {
+                            ::clap_config_file::ClapConfigError::Invalid(format!(
+                                "config_override is not valid {}",
+                                [#(#fmts),*].join(" or ")
+                            ))
+                        })?;
+                    extra.push(Box::new(::config::File::from_str(raw, file_format)));
+                }
+                Self::try_parse_info_with_fs_and_sources(args, &::clap_config_file::RealFs, extra)
+            }
+            /// `parse_info_with_fs` and `parse_info_with_sources` combined.
+            pub fn parse_info_with_fs_and_sources<I, T>(
+                args: I,
+                fs: &dyn ::clap_config_file::ConfigFs,
+                extra: Vec<Box<dyn ::config::Source + Send + Sync>>,
+            ) -> (Self, Option<std::path::PathBuf>, Option<&'static str>)
+            where
+                I: IntoIterator<Item = T>,
+                T: Into<std::ffi::OsString> + Clone,
+            {
+                Self::try_parse_info_with_fs_and_sources(args, fs, extra)
+                    .unwrap_or_else(|e| e.exit())
+            }
+            /// Like `parse_info`, but returns errors instead of printing them and exiting.
+            pub fn try_parse_info() -> Result<
+                (Self, Option<std::path::PathBuf>, Option<&'static str>),
+                ::clap_config_file::ClapConfigError,
+            > {
+                Self::try_parse_info_with_fs(std::env::args_os(), &::clap_config_file::RealFs)
+            }
+            /// Fallible `parse_info_with_fs`.
+            pub fn try_parse_info_with_fs<I, T>(
+                args: I,
+                fs: &dyn ::clap_config_file::ConfigFs,
+            ) -> Result<
+                (Self, Option<std::path::PathBuf>, Option<&'static str>),
+                ::clap_config_file::ClapConfigError,
+            >
+            where
+                I: IntoIterator<Item = T>,
+                T: Into<std::ffi::OsString> + Clone,
+            {
+                Self::try_parse_info_with_fs_and_sources(args, fs, Vec::new())
+            }
+            /// Fallible `parse_info_with_fs_and_sources`.
+            pub fn try_parse_info_with_fs_and_sources<I, T>(
+                args: I,
+                fs: &dyn ::clap_config_file::ConfigFs,
+                extra: Vec<Box<dyn ::config::Source + Send + Sync>>,
+            ) -> Result<
+                (Self, Option<std::path::PathBuf>, Option<&'static str>),
+                ::clap_config_file::ClapConfigError,
+            >
+            where
+                I: IntoIterator<Item = T>,
+                T: Into<std::ffi::OsString> + Clone,
+            {
+                Self::__try_parse_full(Self::command(), args, fs, extra)
+                    .map(|(cfg, path, fmt, _, _)| (cfg, path, fmt))
+            }
+            /// Like `try_parse_info`, but a panic while loading (e.g. deep inside the
+            /// `config` crate) is caught and returned as `ClapConfigError::Panicked`,
+            /// for long-running processes that reload their config.
+            pub fn parse_info_safe() -> Result<
+                (Self, Option<std::path::PathBuf>, Option<&'static str>),
+                ::clap_config_file::ClapConfigError,
+            > {
+                Self::parse_info_safe_with_fs(std::env::args_os(), &::clap_config_file::RealFs)
+            }
+            /// `parse_info_safe` with explicit CLI args and filesystem.
+            pub fn parse_info_safe_with_fs<I, T>(
+                args: I,
+                fs: &dyn ::clap_config_file::ConfigFs,
+            ) -> Result<
+                (Self, Option<std::path::PathBuf>, Option<&'static str>),
+                ::clap_config_file::ClapConfigError,
+            >
+            where
+                I: IntoIterator<Item = T>,
+                T: Into<std::ffi::OsString> + Clone,
+            {
+                // Nothing half-built outlives a panic: the result is all or nothing
+                ::std::panic::catch_unwind(::std::panic::AssertUnwindSafe(|| {
+                    Self::try_parse_info_with_fs(args, fs)
+                }))
+                .unwrap_or_else(|payload| {
+                    Err(::clap_config_file::ClapConfigError::from_panic(payload))
+                })
+            }
+            /// The clap `Command` behind the CLI side (every field's flag plus
+            /// `--config-file` and friends), to customize before parsing with
+            /// `parse_info_from_command`: `MyConfig::command().version("1.2.3")`.
+            pub fn command() -> ::clap::Command {
+                <#cli_ident as ::clap::CommandFactory>::command()
+            }
+            /// Like `parse_info`, parsing the CLI with `cmd`: usually `command()` with
+            /// `about`, `version` or global settings added. Removing or renaming its
+            /// args is not supported.
+            pub fn parse_info_from_command(
+                cmd: ::clap::Command,
+            ) -> (Self, Option<std::path::PathBuf>, Option<&'static str>) {
+                Self::try_parse_info_from_command_with_fs(
+                    cmd,
+                    std::env::args_os(),
+                    &::clap_config_file::RealFs,
+                )
+                .unwrap_or_else(|e| e.exit())
+            }
+            /// `parse_info_from_command` with explicit CLI args and filesystem,
+            /// returning errors instead of exiting.
+            pub fn try_parse_info_from_command_with_fs<I, T>(
+                cmd: ::clap::Command,
+                args: I,
+                fs: &dyn ::clap_config_file::ConfigFs,
+            ) -> Result<
+                (Self, Option<std::path::PathBuf>, Option<&'static str>),
+                ::clap_config_file::ClapConfigError,
+            >
+            where
+                I: IntoIterator<Item = T>,
+                T: Into<std::ffi::OsString> + Clone,
+            {
+                Self::__try_parse_full(cmd, args, fs, Vec::new())
+                    .map(|(cfg, path, fmt, _, _)| (cfg, path, fmt))
+            }
+            /// Like `parse_info`, plus the merged config tree (files, custom sources and
+            /// inline `--config`) as it was before being deserialized into `Self`.
+            /// Handy for spotting typo'd keys that silently fell back to defaults.
+            pub fn parse_info_with_raw() -> (
+                Self,
+                Option<std::path::PathBuf>,
+                Option<&'static str>,
+                ::clap_config_file::serde_json::Value,
+            ) {
+                Self::parse_info_with_raw_fs(std::env::args_os(), &::clap_config_file::RealFs)
+            }
+            /// `parse_info_with_raw` with explicit CLI args and filesystem.
+            pub fn parse_info_with_raw_fs<I, T>(
+                args: I,
+                fs: &dyn ::clap_config_file::ConfigFs,
+            ) -> (
+                Self,
+                Option<std::path::PathBuf>,
+                Option<&'static str>,
+                ::clap_config_file::serde_json::Value,
+            )
+            where
+                I: IntoIterator<Item = T>,
+                T: Into<std::ffi::OsString> + Clone,
+            {
+                Self::__try_parse_full(Self::command(), args, fs, Vec::new())
+                    .and_then(|(cfg, path, fmt, built, _)| {
+                        let raw = built
+                            .try_deserialize::<::clap_config_file::serde_json::Value>()
+                            .map_err(::clap_config_file::ClapConfigError::ConfigDeserializeError)?;
+                        Ok((cfg, path, fmt, raw))
+                    })
+                    .unwrap_or_else(|e| e.exit())
+            }
+            /// Like `parse_info`, plus the `ParseDetails` of this parse: which fields
+            /// came from the CLI and which config files were merged.
+            pub fn parse_info_with_details() -> (
+                Self,
+                Option<std::path::PathBuf>,
+                Option<&'static str>,
+                ::clap_config_file::ParseDetails,
+            ) {
+                Self::try_parse_info_with_details_fs(std::env::args_os(), &::clap_config_file::RealFs)
+                    .unwrap_or_else(|e| e.exit())
+            }
+            /// Fallible `parse_info_with_details`, with explicit CLI args and filesystem.
+            pub fn try_parse_info_with_details_fs<I, T>(
+                args: I,
+                fs: &dyn ::clap_config_file::ConfigFs,
+            ) -> Result<
+                (
+                    Self,
+                    Option<std::path::PathBuf>,
+                    Option<&'static str>,
+                    ::clap_config_file::ParseDetails,
+                ),
+                ::clap_config_file::ClapConfigError,
+            >
+            where
+                I: IntoIterator<Item = T>,
+                T: Into<std::ffi::OsString> + Clone,
+            {
+                Self::__try_parse_full(Self::command(), args, fs, Vec::new())
+                    .map(|(cfg, path, fmt, _, details)| (cfg, path, fmt, details))
+            }
+            /// Every parse method ends up here. Also returns the merged `config::Config`
+            /// and the parse's `ParseDetails`.
+            #[doc(hidden)]
+            pub fn __try_parse_full<I, T>(
+                mut cmd: ::clap::Command,
+                args: I,
+                fs: &dyn ::clap_config_file::ConfigFs,
+                extra: Vec<Box<dyn ::config::Source + Send + Sync>>,
+            ) -> Result<
+                (
+                    Self,
+                    Option<std::path::PathBuf>,
+                    Option<&'static str>,
+                    ::config::Config,
+                    ::clap_config_file::ParseDetails,
+                ),
+                ::clap_config_file::ClapConfigError,
+            >
+            where
+                I: IntoIterator<Item = T>,
+                T: Into<std::ffi::OsString> + Clone,
+            {
+                #parse_info_impl
+            }
+            pub fn parse() -> Self {
+                Self::parse_from(std::env::args_os())
+            }
+            /// Like `parse`, with explicit CLI args (the first one is the binary name).
+            pub fn parse_from<I, T>(args: I) -> Self
+            where
+                I: IntoIterator<Item = T>,
+                T: Into<std::ffi::OsString> + Clone,
+            {
+                Self::try_parse_from(args).unwrap_or_else(|e| e.exit())
+            }
+            /// Like clap's `try_parse_from`: CLI and config errors alike come back as
+            /// a `ClapConfigError` instead of exiting.
+            pub fn try_parse_from<I, T>(args: I) -> Result<Self, ::clap_config_file::ClapConfigError>
+            where
+                I: IntoIterator<Item = T>,
+                T: Into<std::ffi::OsString> + Clone,
+            {
+                let (cfg, _, _) = Self::try_parse_info_with_fs(args, &::clap_config_file::RealFs)?;
+                #validate
+                Ok(cfg)
+            }
+            /// CLI args reproducing this value's CLI-accepting fields (the inverse of parsing),
+            /// e.g. for passing the effective settings on to a child process.
+            /// Config-only and internal fields are skipped.
+            pub fn to_args(&self) -> Vec<String> {
+                #to_args_body
+            }
+            /// Append `to_args()` to `cmd`.
+            pub fn apply_to(&self, cmd: &mut ::std::process::Command) {
+                cmd.args(self.to_args());
+            }
+            /// Write a completion script for `shell` (bash, zsh, fish, ...) to `buf`,
+            /// covering every flag including `--config-file` and friends.
+            pub fn generate_completion(
+                shell: ::clap_config_file::clap_complete::Shell,
+                buf: &mut impl ::std::io::Write,
+            ) {
+                let mut cmd = Self::command();
+                let bin_name = cmd.get_name().to_string();
+                ::clap_config_file::clap_complete::generate(shell, &mut cmd, bin_name, buf);
+            }
+            /// Write a roff man page for the CLI to `w`, with the struct's doc comment
+            /// as its description. Needs clap-config-file's `man` feature.
+            pub fn generate_man_page(w: &mut impl ::std::io::Write) -> ::std::io::Result<()> {
+                #[allow(unused_mut)]
+                let mut cmd = Self::command();
+                #man_about
+                ::clap_config_file::man::render(cmd, w)
+            }
+            /// Write a starter config file in `fmt` (yaml, toml, json, jsonc or json5) holding
+            /// this value's config-file fields, with their help as comments where the format
+            /// has them. `--generate-config` does the same with the default values.
+            pub fn write_config_template(
+                &self,
+                fmt: &str,
+                w: &mut impl ::std::io::Write,
+            ) -> ::std::io::Result<()> {
+                let entries = #template_entries;
+                let text = ::clap_config_file::template::render(fmt, &entries)
+                    .map_err(|e| ::std::io::Error::new(::std::io::ErrorKind::InvalidInput, e))?;
+                w.write_all(text.as_bytes())
+            }
+            /// Write this value to the config file at `path`, in the declared format its
+            /// extension names, as `write_config_template` renders it but without `sensitive`
+            /// fields. An extension that isn't a declared format is an `InvalidInput` error.
+            pub fn save_to(&self, path: impl AsRef<std::path::Path>) -> ::std::io::Result<()> {
+                let path = path.as_ref();
+                let fmt = ::clap_config_file::format::guess_format(path, &[#(#fmts),*])
+                    .ok_or_else(|| {
+                        ::std::io::Error::new(
+                            ::std::io::ErrorKind::InvalidInput,
+                            format!("cannot tell the config format of {}", path.display()),
+                        )
+                    })?;
+                let entries = #saved_entries;
+                let text = ::clap_config_file::template::render(fmt, &entries)
+                    .map_err(|e| ::std::io::Error::new(::std::io::ErrorKind::InvalidInput, e))?;
+                ::std::fs::write(path, text)
+            }
+            /// Every flag and config key this struct accepts (what `--dump-schema` prints).
+            pub fn schema() -> Vec<::clap_config_file::schema::FieldSchema> {
+                vec![#(#schema_entries),*]
+            }
+            /// A JSON Schema for the config file: one property per config key, for
+            /// editor completion and validation via `$schema`.
+            pub fn config_json_schema() -> ::clap_config_file::serde_json::Value {
+                ::clap_config_file::schema::json_schema(stringify!(#struct_ident), &Self::schema())
+            }
+            /// Write a lock file recording this config (as serialized) and `sources`,
+            /// usually the parse's `ParseDetails::merged_config_paths()`, for
+            /// `verify_against_lock`. `sensitive` fields are recorded as digests.
+            pub fn lock_config(
+                &self,
+                path: impl AsRef<std::path::Path>,
+                sources: &[std::path::PathBuf],
+            ) -> Result<(), ::clap_config_file::ClapConfigError> {
+                let mut config = ::clap_config_file::serde_json::to_value(self)
+                    .map_err(|e| ::clap_config_file::ClapConfigError::Invalid(e.to_string()))?;
+                ::clap_config_file::lock::redact(&mut config, &[#(#sensitive_names),*]);
+                ::clap_config_file::lock::write(path.as_ref(), config, sources)
+            }
+            /// Error (`Invalid`, naming the changed keys) unless this config matches the
+            /// one recorded by `lock_config` at `path`.
+            pub fn verify_against_lock(
+                &self,
+                path: impl AsRef<std::path::Path>,
+            ) -> Result<(), ::clap_config_file::ClapConfigError> {
+                let mut config = ::clap_config_file::serde_json::to_value(self)
+                    .map_err(|e| ::clap_config_file::ClapConfigError::Invalid(e.to_string()))?;
+                ::clap_config_file::lock::redact(&mut config, &[#(#sensitive_names),*]);
+                ::clap_config_file::lock::verify(path.as_ref(), &config)
+            }
+            /// Fields whose values differ between `a` and `b`, in declaration order.
+            pub fn diff(a: &Self, b: &Self) -> Vec<::clap_config_file::diff::FieldDiff> {
+                [#(#diff_entries),*].into_iter().flatten().collect()
+            }
+        }
+
+        #auto_short_warning
+        #debug_impl
+        #serialize_impl
+        #from_str_impl
+        #hash_eq_impl
+        #getters_impl
+    };
+
+    Ok(expanded)
+}
+
+/// CLI stand-ins for a `flatten` field's listed sub-fields, named `{field}__{sub}`
+/// in the clap struct and `--{field}.{sub}` on the command line.
+/// An `Option<T>` sub-field's flag takes a plain `T`.
+fn flattened_cli_fields(field: &FieldInfo) -> Vec<FieldInfo> {
+    field
+        .arg_attrs
+        .flatten
+        .iter()
+        .map(|(sub, ty)| {
+            let ident = flattened_ident(field, sub);
+            let ty = generic_arg(ty, "Option").unwrap_or(ty).clone();
+            FieldInfo {
+                member: syn::Member::Named(ident.clone()),
+                ident,
+                ty,
+                arg_attrs: ArgAttributes {
+                    name: Some(format!(
+                        "{}.{}",
+                        field.cli_name(),
+                        sub.to_string().to_kebab_case()
+                    )),
+                    help_text: format!(
+                        "Overrides `{}.{}` from the config file",
+                        field.config_key(),
+                        sub
+                    ),
+                    availability: FieldAvailability::CliAndConfig,
+                    ..Default::default()
+                },
+            }
+        })
+        .collect()
+}
+
+fn flattened_ident(field: &FieldInfo, sub: &syn::Ident) -> syn::Ident {
+    syn::Ident::new(&format!("{}__{}", field.ident, sub), Span::call_site())
+}
+
+/// Formats `clap_config_file::template::render` can write.
+const TEMPLATE_FORMATS: &[&str] = &["yaml", "yml", "toml", "json", "jsonc", "json5"];
+
+/// `Vec<TemplateEntry>` for the config-file fields (deprecated ones left out),
+/// reading each value through `access`. A `rest` field's keys follow the rest.
+fn generate_template_entries(
+    fields: &[FieldInfo],
+    access: impl Fn(&FieldInfo) -> TokenStream2,
+) -> TokenStream2 {
+    let rest = fields.iter().find(|f| f.arg_attrs.rest).map(|f| {
+        let value = access(f);
+        quote! {
+            for (key, value) in &#value {
+                entries.push(::clap_config_file::template::TemplateEntry {
+                    key: ::std::borrow::Cow::Owned(key.clone()),
+                    help: ::std::string::String::new(),
+                    value: ::clap_config_file::serde_json::to_value(value)
+                        .unwrap_or(::clap_config_file::serde_json::Value::Null),
+                });
+            }
+        }
+    });
+    let entries = fields
+        .iter()
+        .filter(|f| {
+            matches!(
+                f.arg_attrs.availability,
+                FieldAvailability::ConfigOnly | FieldAvailability::CliAndConfig
+            ) && f.arg_attrs.deprecated.is_none()
+                && !f.arg_attrs.rest
+        })
+        .map(|f| {
+            let key = f.config_key();
+            let help = match &f.arg_attrs.help_from {
+                Some(expr) => quote!(::std::string::String::from(#expr)),
+                None => {
+                    let text = &f.arg_attrs.help_text;
+                    quote!(::std::string::String::from(#text))
+                }
+            };
+            let value = access(f);
+            // What config loading parses back, not serde's `{secs, nanos}`
+            let value = if f.is_duration_type() {
+                duration_string(f, value)
+            } else {
+                value
+            };
+            quote! {
+                ::clap_config_file::template::TemplateEntry {
+                    key: ::std::borrow::Cow::Borrowed(#key),
+                    help: #help,
+                    value: ::clap_config_file::serde_json::to_value(&#value)
+                        .unwrap_or(::clap_config_file::serde_json::Value::Null),
+                }
+            }
+        });
+    match rest {
+        Some(rest) => quote! {{
+            let mut entries = vec![#(#entries),*];
+            #rest
+            entries
+        }},
+        None => quote!(vec![#(#entries),*]),
+    }
+}
+
+/// `EnvEntry` literals for `--dump-env`: every non-internal, non-positional field of
+/// `final_struct`, named by its first env var or its config key.
+fn generate_env_entries(fields: &[FieldInfo]) -> TokenStream2 {
+    let entries = fields
+        .iter()
+        .filter(|f| {
+            f.arg_attrs.availability != FieldAvailability::Internal
+                && !f.arg_attrs.positional
+                && !f.arg_attrs.rest
+        })
+        .map(|f| {
+            let name = match f.arg_attrs.env.first() {
+                Some(env) => env.clone(),
+                None => f.config_key().to_shouty_snake_case(),
+            };
+            let member = &f.member;
+            // The spelling env loading parses back, not serde's `{secs, nanos}`
+            let value = if f.is_duration_type() {
+                duration_string(f, quote!(final_struct.#member))
+            } else {
+                quote!(final_struct.#member)
+            };
+            let sensitive = f.arg_attrs.sensitive;
+            quote! {
+                ::clap_config_file::dump_env::EnvEntry {
+                    name: #name,
+                    value: ::clap_config_file::serde_json::to_value(&#value)
+                        .unwrap_or(::clap_config_file::serde_json::Value::Null),
+                    sensitive: #sensitive,
+                }
+            }
+        });
+    quote!(vec![#(#entries),*])
+}
+
+/// `diff::compare` calls for `diff()`: every non-internal field of `a` against `b`.
+fn generate_diff_entries(fields: &[FieldInfo]) -> Vec<TokenStream2> {
+    fields
+        .iter()
+        .filter(|f| f.arg_attrs.availability != FieldAvailability::Internal)
+        .map(|f| {
+            let member = &f.member;
+            let name = f.ident.to_string();
+            let sensitive = f.arg_attrs.sensitive;
+            quote!(::clap_config_file::diff::compare(#name, &a.#member, &b.#member, #sensitive))
+        })
+        .collect()
+}
+
+/// Name of the ephemeral clap struct behind `StructName`
+fn cli_struct_ident(struct_ident: &syn::Ident) -> syn::Ident {
+    syn::Ident::new(&format!("__{}_Cli", struct_ident), Span::call_site())
+}
+
+/// The ephemeral clap struct: every CLI-accepting field plus the built-in flags.
+/// Emitted next to the user's struct so both parsing and `command()` can use it.
+fn generate_cli_struct(
+    struct_ident: &syn::Ident,
+    fields: &[FieldInfo],
+    macro_cfg: &MacroConfig,
+) -> TokenStream2 {
+    let cli_ident = cli_struct_ident(struct_ident);
+    let cli_fields = fields
+        .iter()
+        .filter(|f| {
+            !matches!(
+                f.arg_attrs.availability,
+                FieldAvailability::ConfigOnly | FieldAvailability::Internal
+            )
+        })
+        .map(generate_cli_field);
+    let flattened_fields: Vec<FieldInfo> = fields.iter().flat_map(flattened_cli_fields).collect();
+    let cli_fields = cli_fields.chain(flattened_fields.iter().map(generate_cli_field));
+
+    let fmts = &macro_cfg.formats;
+    let config_format_help = format!(
+        "Format of `--config-file -` (stdin) and `--config` [default: {} for stdin, detected for --config]",
+        macro_cfg.formats[0]
+    );
+    // Only offered in the declared formats a template can be written in
+    let template_fmts: Vec<&String> = fmts
+        .iter()
+        .filter(|f| TEMPLATE_FORMATS.contains(&f.as_str()))
+        .collect();
+    let generate_config_arg = match template_fmts.first() {
+        Some(first) => {
+            let help = format!(
+                "Print a starter config file (with defaults) and exit [default: {}]",
+                first
+            );
+            quote!(#[clap(long="generate-config", value_name="FORMAT", num_args=0..=1, require_equals=true, default_missing_value=#first, value_parser=::clap::builder::PossibleValuesParser::new([#(#template_fmts),*]), help=#help)])
+        }
+        None => quote!(#[clap(skip)]),
+    };
+    let cli_extras = quote! {
+        #[clap(long="no-config", default_value_t=false, help="Do not use a config file")]
+        __no_config: bool,
+
+        #[clap(long="config-file", help="Path to the config file; repeat (or comma-separate) to layer files, later wins; optionally as PATH:FORMAT")]
+        __config_file: Vec<std::path::PathBuf>,
+
+        #[clap(long="config-format", value_name="FORMAT", value_parser=::clap::builder::PossibleValuesParser::new([#(#fmts),*]), help=#config_format_help)]
+        __config_format: Option<String>,
+
+        #[clap(long="config", value_name="STRING", help="Inline config (any declared format), layered over the config file")]
+        __config: Option<String>,
+
+        #[clap(long="dump-schema", default_value_t=false, help="Print all flags and config keys as JSON and exit")]
+        __dump_schema: bool,
+
+        #generate_config_arg
+        __generate_config: Option<String>,
+
+        #[clap(long="dump-env", default_value_t=false, help="Print the effective config as NAME=value env lines and exit")]
+        __dump_env: bool,
+
+        #[clap(long="unsafe", default_value_t=false, requires="__dump_env", help="Show sensitive values in --dump-env output")]
+        __unsafe: bool,
+    };
+    let mut after_help = Vec::new();
+    if !macro_cfg.examples.is_empty() {
+        let lines: Vec<String> = macro_cfg
+            .examples
+            .iter()
+            .map(|e| format!("  {}", e))
+            .collect();
+        after_help.push(format!("Examples:\n{}", lines.join("\n")));
+    }
+    // Spell out where env fits in, since `[env: X]` alone doesn't say who wins
+    if fields.iter().any(|f| !f.arg_attrs.env.is_empty()) {
+        after_help.push("Precedence: command line > env > config file > default".to_string());
+    }
+    let after_help = (!after_help.is_empty()).then(|| {
+        let text = after_help.join("\n\n");
+        quote!(#[command(after_help = #text)])
+    });
+    quote! {
+        #[allow(non_camel_case_types)]
+        #[derive(::clap::Parser, ::std::fmt::Debug, ::std::default::Default)]
+        #after_help
+        struct #cli_ident {
+            #cli_extras
+            #(#cli_fields),*
+        }
+    }
+}
+
+/// Generate parse_info: ephemeral CLI + ephemeral config => unify.
+fn generate_parse_info_impl(
+    struct_ident: &syn::Ident,
+    fields: &[FieldInfo],
+    macro_cfg: &MacroConfig,
+) -> TokenStream2 {
+    let base_names = &macro_cfg.base_name;
+    let base_name = &base_names[0];
+    let fmts = &macro_cfg.formats;
+    let fmts_list: Vec<_> = fmts.iter().map(|s| s.as_str()).collect();
+    let first_fmt = fmts_list[0];
+    let format_precedence = macro_cfg.format_precedence;
+    let layered = macro_cfg.layered;
+    let cli_file_layer = macro_cfg.cli_file_layer;
+    let nonempty_check = if macro_cfg.nonempty {
+        quote! {
+            if contents.trim().is_empty() {
+                return Err(::clap_config_file::ClapConfigError::Invalid(format!(
+                    "config file {} is empty",
+                    path.display()
+                )));
+            }
+        }
+    } else {
+        quote!()
+    };
+    // `exclusive_with_config` flags act like --no-config when given on the CLI
+    let exclusive_flags = fields
+        .iter()
+        .filter(|f| f.arg_attrs.exclusive_with_config)
+        .map(|f| {
+            let ident = &f.ident;
+            if f.is_bool_type() {
+                quote!(cli.#ident == Some(true))
+            } else {
+                let cli_value = cli_value(f);
+                quote!(#cli_value.is_some())
+            }
+        });
+    let no_config = quote!(cli.__no_config #(|| #exclusive_flags)*);
+    let require_explicit_check = if macro_cfg.require_explicit {
+        quote! {
+            if cli.__config_file.is_empty() && cli.__config.is_none() && !no_config {
+                return Err(::clap_config_file::ClapConfigError::Invalid(
+                    "no config source given: pass --config-file, --config or --no-config".to_string(),
+                ));
+            }
+        }
+    } else {
+        quote!()
+    };
+    let discover = match (&macro_cfg.cargo_metadata, &macro_cfg.recursive_root) {
+        // Only explicit sources count
+        _ if macro_cfg.require_explicit => quote!(Vec::new()),
+        (None, Some(root)) => quote! {
+            // Recursive mode: every declared-format file in the subtree, sorted by
+            // path, so later paths win per key
+            match fs.current_dir() {
+                Ok(cwd) => ::clap_config_file::walk_files(
+                    fs,
+                    &cwd.join(#root),
+                    ::clap_config_file::RECURSIVE_MAX_DEPTH,
+                )
+                .into_iter()
+                .filter_map(|path| {
+                    let format = ::clap_config_file::format::guess_format(&path, &[#(#fmts_list),*])?;
+                    Some((path, Some(format)))
+                })
+                .collect(),
+                Err(_) => Vec::new(),
+            }
+        },
+        (Some(tool), _) => {
+            let sections = [
+                format!("workspace.metadata.{}", tool),
+                format!("package.metadata.{}", tool),
+            ];
+            quote! {
+                // Cargo.toml metadata mode: package-level keys win over workspace-level
+                if let Some(manifest) = ::clap_config_file::find_upwards(fs, "Cargo.toml") {
+                    match fs.read_to_string(&manifest) {
+                        Ok(contents) => {
+                            for section in [#(#sections),*] {
+                                let table = ::clap_config_file::section::extract(
+                                    &contents,
+                                    ::config::FileFormat::Toml,
+                                    section,
+                                )
+                                .map_err(::clap_config_file::ClapConfigError::ConfigDeserializeError)?;
+                                if let Some(table) = table {
+                                    layers.push(Box::new(table));
+                                    if !merged_paths.contains(&manifest) {
+                                        merged_paths.push(manifest.clone());
+                                    }
+                                    used_path = Some(manifest.clone());
+                                    used_format = Some("toml");
+                                }
+                            }
+                        }
+                        Err(source) => {
+                            return Err(::clap_config_file::ClapConfigError::ConfigReadError {
+                                path: manifest,
+                                source,
+                            });
+                        }
+                    }
+                }
+                Vec::new()
+            }
+        }
+        (None, None) if macro_cfg.fixed_path => quote! {
+            // Absolute config_file_name: that exact file, if it exists
+            {
+                let path = std::path::PathBuf::from(#base_name);
+                if fs.is_file(&path) {
+                    let format = ::clap_config_file::format::guess_format(&path, &[#(#fmts_list),*]);
+                    vec![(path, format)]
+                } else {
+                    Vec::new()
+                }
+            }
+        },
+        (None, None) => {
+            let stop_at = &macro_cfg.stop_at;
+            let find = if macro_cfg.search.is_empty() {
+                quote!(::clap_config_file::find_config(
+                    fs,
+                    &[#(#base_names),*],
+                    &[#(#fmts_list),*],
+                    &[#(#stop_at),*],
+                    #format_precedence,
+                    #layered,
+                ))
+            } else {
+                let search = &macro_cfg.search;
+                quote!(::clap_config_file::search_config(
+                    fs,
+                    &[#(#base_names),*],
+                    &[#(#fmts_list),*],
+                    &[#(#stop_at),*],
+                    &[#(#search),*],
+                    #format_precedence,
+                    #layered,
+                ))
+            };
+            quote! {
+            #find?
+            .into_iter()
+            .map(|path| {
+                let format = ::clap_config_file::format::guess_format(&path, &[#(#fmts_list),*]);
+                (path, format)
+            })
+            .collect()
+            }
+        }
+    };
+
+    let file_source = match macro_cfg.include {
+        true => quote! {
+            ::clap_config_file::include::WithoutInclude(::config::File::from_str(&contents, file_format))
+        },
+        false => quote!(::config::File::from_str(&contents, file_format)),
+    };
+    let add_file_source = match &macro_cfg.wrap_key {
+        Some(key) => quote! {
+            // Unwrap `key:` when the file has it; a file without it is used as is
+            match ::clap_config_file::section::extract(&contents, file_format, #key)
+                .map_err(::clap_config_file::ClapConfigError::ConfigDeserializeError)?
+            {
+                Some(table) => layers.push(Box::new(table)),
+                None => layers.push(Box::new(#file_source)),
+            }
+        },
+        None => quote! {
+            layers.push(Box::new(#file_source));
+        },
+    };
+    let add_included = macro_cfg.include.then(|| {
+        quote! {
+            // Files it `include`s sit just below it, listed among the merged sources
+            let included = ::clap_config_file::include::resolve(
+                fs, &path, &contents, file_format, &[#(#fmts_list),*],
+            )?;
+            for (included_path, contents, file_format) in included {
+                #add_file_source
+                if !merged_paths.contains(&included_path) {
+                    merged_paths.push(included_path);
+                }
+            }
+        }
+    });
+
+    let cli_ident = cli_struct_ident(struct_ident);
+
+    // ephemeral config
+    let cfg_ident = syn::Ident::new(&format!("__{}_Cfg", struct_ident), Span::call_site());
+    let default_template_entries = generate_template_entries(fields, |f| {
+        let ident = &f.ident;
+        quote!(defaults.#ident)
+    });
+    let cfg_field_infos: Vec<&FieldInfo> = fields
+        .iter()
+        .filter(|f| {
+            !matches!(
+                f.arg_attrs.availability,
+                FieldAvailability::CliOnly | FieldAvailability::Internal
+            )
+        })
+        .collect();
+    let cfg_fields = cfg_field_infos.iter().map(|f| generate_config_field(f));
+    let key_aliases = cfg_field_infos
+        .iter()
+        .filter(|f| !f.arg_attrs.rest)
+        .flat_map(|f| {
+            let key = f.config_key();
+            f.config_key_aliases()
+                .into_iter()
+                .map(move |alias| quote!((#alias, #key)))
+        });
+    let default_fns = generate_default_fns(&cfg_field_infos);
+    let deserialize_fns = generate_deserialize_fns(&cfg_field_infos);
+    // serde can't deny unknown fields next to a `flatten`ed `rest` field that takes them
+    let deny_unknown = (macro_cfg.deny_unknown && !fields.iter().any(|f| f.arg_attrs.rest))
+        .then(|| quote!(#[serde(deny_unknown_fields)]));
+    let build_cfg_struct = quote! {
+        #default_fns
+        #deserialize_fns
+
+        #[derive(::serde::Deserialize, ::std::fmt::Debug)]
+        #deny_unknown
+        struct #cfg_ident {
+            #(#cfg_fields),*
+        }
+    };
+
+    let config_defaults = quote! {{
+        let built = ::config::Config::builder()
+            .build()
+            .map_err(::clap_config_file::ClapConfigError::ConfigDeserializeError)?;
+        let ephemeral_cfg: #cfg_ident = built
+            .clone()
+            .try_deserialize()
+            .map_err(::clap_config_file::ClapConfigError::ConfigDeserializeError)?;
+        (built, ephemeral_cfg)
+    }};
+    let on_config_error = match macro_cfg.on_error {
+        ConfigOnError::Fail => {
+            quote!(
+                return Err(::clap_config_file::ClapConfigError::ConfigDeserializeError(
+                    e
+                ))
+            )
+        }
+        ConfigOnError::Warn => quote! {{
+            eprintln!("Warning: ignoring config: {}", e);
+            #config_defaults
+        }},
+        ConfigOnError::Default => quote! {{
+            let _ = e;
+            #config_defaults
+        }},
+    };
+    let merge_by_key_fields: Vec<&FieldInfo> = fields
+        .iter()
+        .filter(|f| f.arg_attrs.merge_key.is_some())
+        .collect();
+    let ephemeral_mut = (!merge_by_key_fields.is_empty()).then(|| quote!(mut));
+    let merge_by_key = merge_by_key_fields.iter().map(|f| {
+        let ident = &f.ident;
+        let config_key = f.config_key();
+        let key = &f.arg_attrs.merge_key;
+        quote! {
+            // Built config replaced the list whole; redo it layer by layer
+            if let Some(items) = ::clap_config_file::merge::merge_by_key(&layers, #config_key, #key)
+                .map_err(::clap_config_file::ClapConfigError::ConfigDeserializeError)?
+            {
+                ephemeral_cfg.#ident = ::clap_config_file::serde_json::from_value(items).map_err(|e| {
+                    ::clap_config_file::ClapConfigError::ConfigDeserializeError(
+                        ::config::ConfigError::Message(format!("{}: {}", #config_key, e)),
+                    )
+                })?;
+            }
+        }
+    });
+    let merge_by_key = quote!(#(#merge_by_key)*);
+    let unify_stmts = fields.iter().map(unify_field);
+    let env_entries = generate_env_entries(fields);
+    let (requires_if_snapshots, requires_if_checks) = generate_requires_if_checks(fields);
+    let cli_presence = fields
+        .iter()
+        .filter(|f| {
+            !matches!(
+                f.arg_attrs.availability,
+                FieldAvailability::ConfigOnly | FieldAvailability::Internal
+            )
+        })
+        .map(|f| {
+            let name = f.ident.to_string();
+            let cli_value = cli_value(f);
+            quote!((#name, #cli_value.is_some()))
+        });
+    let group_checks = generate_group_checks(fields);
+    let required_checks = generate_required_checks(fields);
+    let deprecation_warnings = generate_deprecation_warnings(fields);
+
+    let env_filter = if macro_cfg.env_ignore_empty {
+        quote!(.filter(|v| !v.is_empty()))
+    } else {
+        quote!()
+    };
+    // Only env lookup stays generated (it bakes in config_file_env_ignore_empty);
+    // the rest lives in the runtime crate
+    let inline_helpers = quote! {
+        /// Env lookup shared by env fallbacks and presence checks.
+        #[allow(dead_code)]
+        fn __inline_env_var(name: &str) -> Option<String> {
+            std::env::var(name).ok() #env_filter
+        }
+    };
+
+    quote! {
+        #build_cfg_struct
+
+        // What `Parser::try_parse_from` does, with a `cmd` the caller may have customized
+        let mut matches = cmd
+            .try_get_matches_from_mut(args)
+            .map_err(::clap_config_file::ClapConfigError::Cli)?;
+        let cli = <#cli_ident as ::clap::FromArgMatches>::from_arg_matches_mut(&mut matches)
+            .map_err(|e| ::clap_config_file::ClapConfigError::Cli(e.format(&mut cmd)))?;
+        if cli.__dump_schema {
+            return Err(::clap_config_file::ClapConfigError::Display(format!(
+                "{}\n",
+                ::clap_config_file::schema::to_json(&#struct_ident::schema())
+            )));
+        }
+        if let Some(fmt) = &cli.__generate_config {
+            // Every config field at its default: nothing is read
+            let defaults: #cfg_ident = ::config::Config::builder()
+                .build()
+                .and_then(|c| c.try_deserialize())
+                .map_err(::clap_config_file::ClapConfigError::ConfigDeserializeError)?;
+            let entries = #default_template_entries;
+            let text = ::clap_config_file::template::render(fmt, &entries)
+                .map_err(::clap_config_file::ClapConfigError::Invalid)?;
+            return Err(::clap_config_file::ClapConfigError::Display(text));
+        }
+        let no_config = #no_config;
+        let (dump_env, show_sensitive) = (cli.__dump_env, cli.__unsafe);
+        #require_explicit_check
+
+        #inline_helpers
+
+        let mut used_path: Option<std::path::PathBuf> = None;
+        let mut merged_paths: Vec<std::path::PathBuf> = Vec::new();
+        let mut used_format: Option<&'static str> = None;
+
+        // Every source, lowest priority first; built together once collected
+        let mut layers: Vec<Box<dyn ::config::Source + Send + Sync>> = Vec::new();
+        if !no_config {
+            // Explicit files replace discovery (or layer over it, per config_file_cli_file_mode);
+            // each file's format is detected independently
+            let stdin_format: &'static str = [#(#fmts_list),*]
+                .into_iter()
+                .find(|f| cli.__config_format.as_deref() == Some(*f))
+                .unwrap_or(#first_fmt);
+            let explicit: Vec<(std::path::PathBuf, Option<&'static str>)> = cli
+                .__config_file
+                .iter()
+                // Split before expanding, so a comma inside a $VAR value stays put
+                .flat_map(|arg| ::clap_config_file::split_path_list(arg))
+                .map(|path| {
+                    let path = ::clap_config_file::expand_path(&path, fs)
+                        .map_err(::clap_config_file::ClapConfigError::Invalid)?;
+                    Ok(match ::clap_config_file::format::split_format(&path, &[#(#fmts_list),*]) {
+                        // Stdin has no extension to go by
+                        (path, None) if path.as_os_str() == "-" => (path, Some(stdin_format)),
+                        (path, None) if ::clap_config_file::http::is_url(&path) => {
+                            let format = ::clap_config_file::http::guess_format(&path, &[#(#fmts_list),*]);
+                            (path, format)
+                        }
+                        other => other,
+                    })
+                })
+                .collect::<Result<_, ::clap_config_file::ClapConfigError>>()?;
+            let explicit_paths: Vec<std::path::PathBuf> =
+                explicit.iter().map(|(path, _)| path.clone()).collect();
+            let chosen = if explicit.is_empty() || #cli_file_layer {
+                let mut discovered: Vec<(std::path::PathBuf, Option<&'static str>)> = { #discover };
+                discovered.extend(explicit);
+                discovered
+            } else {
+                explicit
+            };
+            // Later sources win per key; report the last (highest-priority) one.
+            for (path, format) in chosen {
+                let explicit = explicit_paths.contains(&path);
+                let format = format.and_then(|f| Some((f, ::clap_config_file::format::file_format(f)?)));
+                let is_stdin = explicit && path.as_os_str() == "-";
+                let is_url = explicit && ::clap_config_file::http::is_url(&path);
+                // No extension at all (`.myapprc`) is sniffed below; an unknown one is an error
+                if format.is_none() && path.extension().is_some() && !is_url {
+                    return Err(::clap_config_file::ClapConfigError::FormatGuessFailed(path));
+                }
+                let read = if is_stdin {
+                    fs.read_stdin()
+                } else if is_url {
+                    ::clap_config_file::http::fetch(&path)
+                } else {
+                    fs.read_to_string(&path)
+                };
+                let contents = match read {
+                    Ok(contents) => contents,
+                    // Found (or named) but unreadable, e.g. permissions: don't fall back to defaults
+                    Err(source) => {
+                        return Err(::clap_config_file::ClapConfigError::ConfigReadError { path, source });
+                    }
+                };
+                #nonempty_check
+                let (fmt, file_format) = match format.or_else(|| {
+                    // An empty file is empty in any format
+                    if contents.trim().is_empty() {
+                        let fmt = #first_fmt;
+                        return Some((fmt, ::clap_config_file::format::file_format(fmt)?));
+                    }
+                    ::clap_config_file::format::detect_format(&contents, &[#(#fmts_list),*])
+                }) {
+                    Some(found) => found,
+                    None => return Err(::clap_config_file::ClapConfigError::FormatGuessFailed(path)),
+                };
+                #add_included
+                #add_file_source
+                used_format = Some(fmt);
+                if is_stdin {
+                    used_path = None;
+                } else if is_url {
+                    // Not a path; still listed (as given) among the merged sources
+                    merged_paths.push(path);
+                    used_path = None;
+                } else {
+                    merged_paths.push(path.clone());
+                    used_path = Some(path);
+                }
+            }
+        }
+
+        // Custom sources sit above files but below inline --config
+        layers.extend(extra);
+
+        // Inline --config: kept verbatim (newlines and indentation matter for YAML/TOML)
+        if let Some(ref raw) = cli.__config {
+            // `--config-format` narrows detection to that one format
+            let candidates: Vec<&'static str> = [#(#fmts_list),*]
+                .into_iter()
+                .filter(|f| cli.__config_format.as_deref().map_or(true, |named| named == *f))
+                .collect();
+            match ::clap_config_file::format::detect_format(raw, &candidates) {
+                Some((fmt, file_format)) => {
+                    layers.push(Box::new(::config::File::from_str(raw, file_format)));
+                    if used_format.is_none() {
+                        used_format = Some(fmt);
+                    }
+                }
+                None => {
+                    return Err(::clap_config_file::ClapConfigError::Invalid(format!(
+                        "--config is not valid {}",
+                        candidates.join(" or ")
+                    )));
+                }
+            }
+        }
+
+        // Every accepted spelling of a key merges (and is looked up) as the key itself
+        let layers: Vec<Box<dyn ::config::Source + Send + Sync>> = layers
+            .into_iter()
+            .map(|source| -> Box<dyn ::config::Source + Send + Sync> {
+                Box::new(::clap_config_file::merge::CanonicalKeys {
+                    source,
+                    aliases: &[#(#key_aliases),*],
+                })
+            })
+            .collect();
+        let loaded = ::config::Config::builder().add_source(layers.clone()).build().and_then(|built| {
+            let ephemeral_cfg: #cfg_ident = built.clone().try_deserialize()?;
+            Ok((built, ephemeral_cfg))
+        });
+        let (built, #ephemeral_mut ephemeral_cfg) = match loaded {
+            Ok(loaded) => loaded,
+            Err(e) => #on_config_error,
+        };
+        #merge_by_key
+
+        #required_checks
+        #group_checks
+        #deprecation_warnings
+
+        let present: &[(&'static str, bool)] = &[#(#cli_presence),*];
+        let details = ::clap_config_file::ParseDetails {
+            cli_set: present.iter().filter(|(_, p)| *p).map(|(n, _)| *n).collect(),
+            merged_paths,
+        };
+
+        #requires_if_snapshots
+        let final_struct = #struct_ident {
+            #(#unify_stmts),*
+        };
+        #requires_if_checks
+        if dump_env {
+            let entries = #env_entries;
+            return Err(::clap_config_file::ClapConfigError::Display(
+                ::clap_config_file::dump_env::render(&entries, show_sensitive),
+            ));
+        }
+        Ok((final_struct, used_path, used_format, built, details))
+    }
+}
+
+/// `required` fields: error unless the CLI, env or config gave a value.
+fn generate_required_checks(fields: &[FieldInfo]) -> TokenStream2 {
+    let checks = fields.iter().filter(|f| f.arg_attrs.required).map(|f| {
+        let mut sources = Vec::new();
+        if !matches!(f.arg_attrs.availability, FieldAvailability::ConfigOnly) {
+            sources.push(if f.arg_attrs.positional {
+                format!("the {} argument", f.cli_name().to_uppercase())
+            } else {
+                format!("--{}", f.cli_name())
+            });
+        }
+        sources.extend(f.arg_attrs.env.iter().map(|e| format!("${}", e)));
+        if !matches!(f.arg_attrs.availability, FieldAvailability::CliOnly) {
+            sources.push(format!("config key `{}`", f.config_key()));
+        }
+        let msg = format!(
+            "missing required field `{}`; set it with {}",
+            f.cli_name(),
+            sources.join(" or ")
+        );
+        let present = presence_expr(f);
+        quote! {
+            if !(#present) {
+                return Err(::clap_config_file::ClapConfigError::Invalid(#msg.to_string()));
+            }
+        }
+    });
+    quote!(#(#checks)*)
+}
+
+/// All-or-nothing groups: members may come from CLI or config, but either
+/// every member is present or none is.
+fn generate_group_checks(fields: &[FieldInfo]) -> TokenStream2 {
+    let mut groups: Vec<(&str, Vec<&FieldInfo>)> = Vec::new();
+    for f in fields {
+        if let Some(group) = &f.arg_attrs.mutually_required_with {
+            match groups.iter_mut().find(|(g, _)| g == group) {
+                Some((_, members)) => members.push(f),
+                None => groups.push((group, vec![f])),
+            }
+        }
+    }
+
+    let checks = groups.iter().map(|(_, members)| {
+        let names: Vec<String> = members.iter().map(|f| f.cli_name()).collect();
+        let all_names = names.join(", ");
+        let presence = members.iter().map(|f| presence_expr(f));
+        quote! {
+            {
+                let present: &[(&str, bool)] = &[#((#names, #presence)),*];
+                if present.iter().any(|(_, p)| *p) && !present.iter().all(|(_, p)| *p) {
+                    let missing: Vec<&str> = present
+                        .iter()
+                        .filter(|(_, p)| !*p)
+                        .map(|(n, _)| *n)
+                        .collect();
+                    return Err(::clap_config_file::ClapConfigError::Invalid(format!(
+                        "{} must be provided together; missing: {}",
+                        #all_names,
+                        missing.join(", ")
+                    )));
+                }
+            }
+        }
+    });
+    quote!(#(#checks)*)
+}
+
+/// One stderr warning per deprecated field that was set on the CLI or in config.
+fn generate_deprecation_warnings(fields: &[FieldInfo]) -> TokenStream2 {
+    let warnings = fields.iter().filter_map(|f| {
+        let note = f.arg_attrs.deprecated.as_ref()?;
+        let key = f.config_key();
+        let from_cli = match f.arg_attrs.availability {
+            FieldAvailability::ConfigOnly => quote!(None),
+            _ => {
+                let flag = format!("--{}", f.cli_name());
+                let cli_value = cli_value(f);
+                quote!(#cli_value.as_ref().map(|_| #flag))
+            }
+        };
+        let from_cfg = match f.arg_attrs.availability {
+            FieldAvailability::CliOnly => quote!(None),
+            _ => {
+                let what = format!("config key `{}`", key);
+                quote!(built.get::<::config::Value>(#key).ok().map(|_| #what))
+            }
+        };
+        Some(quote! {
+            if let Some(used) = #from_cli.or(#from_cfg) {
+                eprintln!("Warning: {} is deprecated: {}", used, #note);
+            }
+        })
+    });
+    quote!(#(#warnings)*)
+}
+
+/// Whether a field was given a value by the CLI, env or config (not a default).
+fn presence_expr(f: &FieldInfo) -> TokenStream2 {
+    let key = f.config_key();
+    let from_cli = match f.arg_attrs.availability {
+        FieldAvailability::ConfigOnly => quote!(false),
+        _ => {
+            let cli_value = cli_value(f);
+            quote!(#cli_value.is_some())
+        }
+    };
+    let env = &f.arg_attrs.env;
+    let from_env = quote!(false #(|| __inline_env_var(#env).is_some())*);
+    let from_cfg = match f.arg_attrs.availability {
+        FieldAvailability::CliOnly => quote!(false),
+        _ => {
+            let aliases = f.config_key_aliases();
+            quote!([#key #(, #aliases)*].iter().any(|k| built.get::<::config::Value>(k).is_ok()))
+        }
+    };
+    quote!(#from_cli || #from_env || #from_cfg)
+}
+
+/// `requires_if` checks, run on the unified struct so the trigger value may come
+/// from any source. The trigger field's type must implement `Display`.
+///
+/// Returns (presence snapshots taken before `cli` is consumed, the checks themselves).
+fn generate_requires_if_checks(fields: &[FieldInfo]) -> (TokenStream2, TokenStream2) {
+    let (snapshots, checks): (Vec<_>, Vec<_>) = fields
+        .iter()
+        .flat_map(|f| f.arg_attrs.requires_if.iter())
+        .enumerate()
+        .map(|(i, (trigger, value, required))| {
+            let find = |name: &str| fields.iter().find(|f| f.ident == name).unwrap();
+            let trigger = find(trigger);
+            let required = find(required);
+            let trigger_member = &trigger.member;
+            let actual = if trigger.option_inner_type().is_some() {
+                quote!(final_struct.#trigger_member.as_ref().map(|v| v.to_string()))
+            } else {
+                quote!(Some(final_struct.#trigger_member.to_string()))
+            };
+            let present = presence_expr(required);
+            let present_ident = syn::Ident::new(&format!("__requires_if_{}", i), Span::call_site());
+            let msg = format!(
+                "{} is required when {} is {:?}",
+                required.cli_name(),
+                trigger.cli_name(),
+                value
+            );
+            let snapshot = quote!(let #present_ident = #present;);
+            let check = quote! {
+                if #actual.as_deref() == Some(#value) && !#present_ident {
+                    return Err(::clap_config_file::ClapConfigError::Invalid(#msg.to_string()));
+                }
+            };
+            (snapshot, check)
+        })
+        .unzip();
+    (quote!(#(#snapshots)*), quote!(#(#checks)*))
+}
+
+/// `value` (one element, for lists) as a CLI argument: `Display`, or the
+/// possible-value name for a `value_enum`.
+fn arg_string(field: &FieldInfo, value: TokenStream2) -> TokenStream2 {
+    if field.is_duration_type() {
+        quote!(format!("{}ms", #value.as_millis()))
+    } else if field.arg_attrs.value_enum {
+        quote! {
+            ::clap::ValueEnum::to_possible_value(&#value)
+                .map(|p| p.get_name().to_string())
+                .unwrap_or_default()
+        }
+    } else {
+        quote!(#value.to_string())
+    }
+}
+
+/// Body of `to_args()`: `--flag value` per set field, then positionals in index order.
+fn generate_to_args_body(fields: &[FieldInfo]) -> TokenStream2 {
+    let cli_fields: Vec<&FieldInfo> = fields
+        .iter()
+        .filter(|f| {
+            !matches!(
+                f.arg_attrs.availability,
+                FieldAvailability::ConfigOnly | FieldAvailability::Internal
+            )
+        })
+        .collect();
+
+    let flags = cli_fields
+        .iter()
+        .filter(|f| !f.arg_attrs.positional)
+        .map(|f| {
+            let member = &f.member;
+            let flag = format!("--{}", f.cli_name());
+            let v = arg_string(f, quote!(v));
+            if f.arg_attrs.count {
+                quote! {
+                    for _ in 0..self.#member {
+                        args.push(#flag.to_string());
+                    }
+                }
+            } else if f.map_types().is_some() {
+                let pair = if f.arg_attrs.num_args.unwrap_or(1) == 1 {
+                    quote!(args.push(format!("{}={}", k, v));)
+                } else {
+                    quote!(args.extend([k.to_string(), v.to_string()]);)
+                };
+                quote! {
+                    for (k, v) in self.#member.iter() {
+                        args.push(#flag.to_string());
+                        #pair
+                    }
+                }
+            } else if f.is_bool_type() {
+                // Absent means false, unless the default is true
+                let default_true = f
+                    .arg_attrs
+                    .default_value
+                    .as_deref()
+                    .is_some_and(|dv| dv.eq_ignore_ascii_case("true"))
+                    || matches!(
+                        &f.arg_attrs.default_value_t,
+                        Some(syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Bool(b), .. })) if b.value
+                    );
+                let off = if default_true {
+                    let off = match f.arg_attrs.negatable {
+                        true => format!("--no-{}", f.cli_name()),
+                        false => format!("{}=false", flag),
+                    };
+                    quote!(else { args.push(#off.to_string()); })
+                } else {
+                    quote!()
+                };
+                quote! {
+                    if self.#member {
+                        args.push(#flag.to_string());
+                    } #off
+                }
+            } else if f.is_vec_type() {
+                quote! {
+                    for v in self.#member.iter() {
+                        args.push(#flag.to_string());
+                        args.push(#v);
+                    }
+                }
+            } else if f.option_vec_inner_type().is_some() {
+                quote! {
+                    for v in self.#member.iter().flatten() {
+                        args.push(#flag.to_string());
+                        args.push(#v);
+                    }
+                }
+            } else if f.option_inner_type().is_some() {
+                quote! {
+                    if let Some(v) = self.#member.as_ref() {
+                        args.push(#flag.to_string());
+                        args.push(#v);
+                    }
+                }
+            } else {
+                let value = arg_string(f, quote!(self.#member));
+                quote! {
+                    args.push(#flag.to_string());
+                    args.push(#value);
+                }
+            }
+        });
+
+    let mut positionals: Vec<&&FieldInfo> = cli_fields
+        .iter()
+        .filter(|f| f.arg_attrs.positional && !f.arg_attrs.trailing)
+        .collect();
+    positionals.sort_by_key(|f| f.arg_attrs.index);
+    let positionals = positionals.iter().map(|f| {
+        let member = &f.member;
+        let v = arg_string(f, quote!(v));
+        if f.option_vec_inner_type().is_some() {
+            quote!(positionals.extend(self.#member.iter().flatten().map(|v| #v));)
+        } else if f.is_vec_type() || f.option_inner_type().is_some() {
+            quote!(positionals.extend(self.#member.iter().map(|v| #v));)
+        } else {
+            let value = arg_string(f, quote!(self.#member));
+            quote!(positionals.push(#value);)
+        }
+    });
+    // clap sends everything after `--` to a `last` arg, so other positionals
+    // have to go before it
+    let trailing = cli_fields.iter().find(|f| f.arg_attrs.trailing).map(|f| {
+        let member = &f.member;
+        let v = arg_string(f, quote!(v));
+        quote! {
+            args.extend(positionals.drain(..));
+            if !self.#member.is_empty() {
+                args.push("--".to_string());
+                args.extend(self.#member.iter().map(|v| #v));
+            }
+        }
+    });
+
+    quote! {
+        #[allow(unused_mut)]
+        let mut args: Vec<String> = Vec::new();
+        #(#flags)*
+        #[allow(unused_mut)]
+        let mut positionals: Vec<String> = Vec::new();
+        #(#positionals)*
+        #trailing
+        // `--` keeps a multi-value flag from swallowing them
+        if !positionals.is_empty() {
+            args.push("--".to_string());
+            args.extend(positionals);
+        }
+        args
+    }
+}
+
+/// `FieldSchema` literals for `schema()`, skipping internal fields.
+fn generate_schema_entries(fields: &[FieldInfo]) -> Vec<TokenStream2> {
+    fn opt_str(v: Option<String>) -> TokenStream2 {
+        match v {
+            Some(s) => quote!(Some(#s)),
+            None => quote!(None),
+        }
+    }
+    fields
+        .iter()
+        .filter(|f| f.arg_attrs.availability != FieldAvailability::Internal)
+        .map(|f| {
+            let attrs = &f.arg_attrs;
+            let field = f.ident.to_string();
+            let has_cli = attrs.availability != FieldAvailability::ConfigOnly;
+            let has_config = attrs.availability != FieldAvailability::CliOnly;
+            let long = opt_str((has_cli && !attrs.positional).then(|| f.cli_name()));
+            let short = match attrs.short.filter(|_| has_cli) {
+                Some(c) => quote!(Some(#c)),
+                None => quote!(None),
+            };
+            let positional = attrs.positional;
+            let config_key = opt_str((has_config && !attrs.rest).then(|| f.config_key()));
+            let ty_tokens = &f.ty;
+            let ty = quote!(#ty_tokens).to_string().replace(' ', "");
+            let default = match (&attrs.default_value, &attrs.default_value_t) {
+                (Some(dv), _) => quote!(Some(::std::string::String::from(#dv))),
+                (None, Some(expr)) => {
+                    let value = default_t_string(f, expr);
+                    quote!(Some(#value))
+                }
+                (None, None) => quote!(None),
+            };
+            let help = match &attrs.help_from {
+                Some(expr) => quote!(::std::string::String::from(#expr)),
+                None => {
+                    let text = &attrs.help_text;
+                    quote!(::std::string::String::from(#text))
+                }
+            };
+            let availability = match attrs.availability {
+                FieldAvailability::CliOnly => "cli_only",
+                FieldAvailability::ConfigOnly => "config_only",
+                _ => "cli_and_config",
+            };
+            quote! {
+                ::clap_config_file::schema::FieldSchema {
+                    field: #field,
+                    long: #long,
+                    short: #short,
+                    positional: #positional,
+                    config_key: #config_key,
+                    ty: #ty,
+                    default: #default,
+                    help: #help,
+                    availability: #availability,
+                }
+            }
+        })
+        .collect()
+}
+
+/// A Duration field's `value` in the `"1500ms"` spelling parsing reads back: a
+/// `String`, or an `Option`/`Vec` of them.
+fn duration_string(field: &FieldInfo, value: TokenStream2) -> TokenStream2 {
+    if field.option_inner_type().is_some() {
+        quote!(#value.map(|d| format!("{}ms", d.as_millis())))
+    } else if field.is_vec_type() {
+        quote!(#value.iter().map(|d| format!("{}ms", d.as_millis())).collect::<Vec<_>>())
+    } else {
+        quote!(format!("{}ms", #value.as_millis()))
+    }
+}
+
+/// A `default_value_t` expression evaluated to the `String` help and the schema
+/// show: as the CLI spells it for durations and `value_enum`s, else serialized.
+fn default_t_string(field: &FieldInfo, expr: &syn::Expr) -> TokenStream2 {
+    let ty = &field.ty;
+    let value = if field.is_duration_type() {
+        duration_string(field, quote!(__default))
+    } else if field.arg_attrs.value_enum
+        && field.option_inner_type().is_none()
+        && !field.is_vec_type()
+    {
+        arg_string(field, quote!(__default))
+    } else {
+        quote!(__default)
+    };
+    quote!({
+        let __default: #ty = #expr;
+        ::clap_config_file::schema::default_string(&#value)
+    })
+}
+
+/// The module with `parse_duration` and `deserialize` for a Duration field.
+fn duration_mod(field: &FieldInfo) -> TokenStream2 {
+    if field.arg_attrs.humantime {
+        quote!(::clap_config_file::duration::humantime)
+    } else {
+        quote!(::clap_config_file::duration)
+    }
+}
+
+/// clap `value_parser` for the field: the user's `value_parser`, else the Duration parser.
+fn value_parser_attr(field: &FieldInfo) -> TokenStream2 {
+    if let Some(parser) = &field.arg_attrs.value_parser {
+        quote!(value_parser = #parser,)
+    } else if field.is_duration_type() {
+        let module = duration_mod(field);
+        quote!(value_parser = #module::parse_duration,)
+    } else if field.arg_attrs.value_enum {
+        quote!(value_enum,)
+    } else {
+        quote!()
+    }
+}
+
+/// Generate ephemeral CLI field if field is not config_only
+fn generate_cli_field(field: &FieldInfo) -> TokenStream2 {
+    let ident = &field.ident;
+    let final_name = field.cli_name();
+    let name_lit = LitStr::new(&final_name, Span::call_site());
+    // cli_only fields have no config layer, so clap applies their default.
+    // Otherwise the default lives in the ephemeral config (see generate_default_fns):
+    // a clap default would always be `Some` and mask env/config values.
+    let clap_default = field.arg_attrs.availability == FieldAvailability::CliOnly;
+    let mut notes = Vec::new();
+    if !field.arg_attrs.env.is_empty() {
+        notes.push(format!("[env: {}]", field.arg_attrs.env.join(", ")));
+    }
+    if let (false, Some(dv)) = (clap_default, &field.arg_attrs.default_value) {
+        notes.push(format!("[default: {}]", dv));
+    }
+    let help_notes = (!notes.is_empty()).then(|| notes.join(" "));
+    // clap never sees a typed default, so it's noted for cli_only fields too. It's
+    // shown evaluated, so the help is built when the command is
+    let typed_note = field.arg_attrs.default_value_t.as_ref().map(|expr| {
+        let value = default_t_string(field, expr);
+        match &help_notes {
+            Some(notes) => quote!(format!("{} [default: {}]", #notes, #value)),
+            None => quote!(format!("[default: {}]", #value)),
+        }
+    });
+    let help_attr = if let Some(notes) = typed_note {
+        let help = match &field.arg_attrs.help_from {
+            Some(expr) => quote!(#expr),
+            None => {
+                let text = &field.arg_attrs.help_text;
+                quote!(#text)
+            }
+        };
+        let long_help_attr = field
+            .arg_attrs
+            .long_help
+            .as_ref()
+            .map(|long| quote!(long_help = format!("{}\n\n{}", #long, #notes),));
+        quote! {
+            help = {
+                let help: &str = &#help;
+                match help.is_empty() {
+                    true => #notes,
+                    false => format!("{} {}", help, #notes),
+                }
+            },
+            #long_help_attr
+        }
+    } else if let Some(expr) = &field.arg_attrs.help_from {
+        // An expression, not a literal: it's evaluated when the command is built
+        match help_notes {
+            Some(notes) => quote!(help = format!("{} {}", #expr, #notes),),
+            None => quote!(help = #expr,),
+        }
+    } else {
+        let mut help_text = field.arg_attrs.help_text.clone();
+        if let Some(note) = &help_notes {
+            if !help_text.is_empty() {
+                help_text.push(' ');
+            }
+            help_text.push_str(note);
+        }
+        // `-h` shows the first doc paragraph, `--help` all of them
+        let long_help_attr = match &field.arg_attrs.long_help {
+            Some(long) => {
+                let long = match &help_notes {
+                    Some(note) => format!("{}\n\n{}", long, note),
+                    None => long.clone(),
+                };
+                quote!(long_help=#long,)
+            }
+            None => quote!(),
+        };
+        if help_text.is_empty() {
+            quote!()
+        } else {
+            let help_lit = LitStr::new(&help_text, Span::call_site());
+            quote!(help=#help_lit, #long_help_attr)
+        }
+    };
+    // Resolved to the other fields' idents (clap's arg ids) by parse_fields
+    let conflicts = &field.arg_attrs.conflicts_with;
+    let requires = &field.arg_attrs.requires;
+    let hide = field.arg_attrs.hide.then(|| quote!(hide = true,));
+    let help_attr =
+        quote!(#help_attr #(conflicts_with = #conflicts,)* #(requires = #requires,)* #hide);
+
+    if field.arg_attrs.positional {
+        // For positional arguments
+        let index_attr = match field.arg_attrs.index {
+            Some(idx) => quote!(index = #idx,),
+            None => quote!(),
+        };
+        // Typed like the field, so clap parses each element (`Vec<u16>`, ...)
+        let vec_inner = field
+            .vec_inner_type()
+            .or_else(|| field.option_vec_inner_type());
+        let cli_ty = match (vec_inner, field.option_inner_type()) {
+            (Some(inner), _) => quote!(Option<Vec<#inner>>),
+            (None, Some(inner)) => quote!(Option<#inner>),
+            (None, None) => {
+                let ty = &field.ty;
+                quote!(Option<#ty>)
+            }
+        };
+        let parser_attr = value_parser_attr(field);
+        let last_attr = field.arg_attrs.trailing.then(|| quote!(last = true,));
+        if vec_inner.is_some() {
+            quote! {
+                #[clap(value_name=#name_lit, #index_attr #last_attr num_args=1.., action=::clap::ArgAction::Append, #parser_attr #help_attr)]
+                #ident: #cli_ty
+            }
+        } else {
+            quote! {
+                #[clap(value_name=#name_lit, #index_attr #parser_attr #help_attr)]
+                #ident: #cli_ty
+            }
+        }
+    } else {
+        // short?
+        let short_attr = if let Some(ch) = field.arg_attrs.short {
+            quote!(short=#ch,)
+        } else {
+            quote!()
+        };
+        let visible = &field.arg_attrs.visible_aliases;
+        let hidden = field
+            .arg_attrs
+            .aliases
+            .iter()
+            .filter(|a| !visible.contains(a));
+        // Bools always require `=` already (`--flag=false`)
+        let require_equals = (field.arg_attrs.require_equals && !field.is_bool_type())
+            .then(|| quote!(require_equals = true,));
+        let short_attr =
+            quote!(#short_attr #(alias = #hidden,)* #(visible_alias = #visible,)* #require_equals);
+
+        let value_names_attr = if field.arg_attrs.value_names.is_empty() {
+            quote!()
+        } else {
+            let names = &field.arg_attrs.value_names;
+            quote!(value_names = [#(#names),*],)
+        };
+
+        if field.arg_attrs.count {
+            // A bare `u8`: clap's Count action stores nothing else
+            return quote! {
+                #[clap(long=#name_lit, #short_attr action=::clap::ArgAction::Count, #help_attr)]
+                #ident: u8
+            };
+        }
+
+        if field.map_types().is_some() {
+            // Collected as raw strings; unify parses them into map entries.
+            // num_args = 1 (default) => "KEY=VALUE", num_args = 2 => "KEY VALUE"
+            let num_args = field.arg_attrs.num_args.unwrap_or(1);
+            let value_names_attr = if field.arg_attrs.value_names.is_empty() && num_args == 1 {
+                quote!(value_name = "KEY=VALUE",)
+            } else {
+                value_names_attr
+            };
+            return quote! {
+                #[clap(long=#name_lit, #short_attr num_args=#num_args, #value_names_attr action=::clap::ArgAction::Append, #help_attr)]
+                #ident: Option<Vec<String>>
+            };
+        }
+
+        if field.is_bool_type() {
+            // `--no-{name}`: a plain switch, read by `cli_value` before the flag itself
+            let negation = field.arg_attrs.negatable.then(|| {
+                let no_ident = negation_ident(field);
+                let no_name = format!("no-{}", field.cli_name());
+                let no_help = format!("Set --{} to false", field.cli_name());
+                let arg_id = ident.to_string();
+                let hide = field.arg_attrs.hide.then(|| quote!(hide = true,));
+                quote! {
+                    ,
+                    #[clap(long=#no_name, action=::clap::ArgAction::SetTrue, conflicts_with=#arg_id, help=#no_help, #hide)]
+                    #no_ident: bool
+                }
+            });
+            // Handle bool default_value "true"/"false"
+            let bool_default = field
+                .arg_attrs
+                .default_value
+                .as_ref()
+                .filter(|_| clap_default);
+            if let Some(dv) = bool_default {
+                let is_true = dv.eq_ignore_ascii_case("true");
+                let is_false = dv.eq_ignore_ascii_case("false");
+                if !is_true && !is_false {
+                    let msg = format!(
+                        "For bool field, default_value must be \"true\" or \"false\", got {}",
+                        dv
+                    );
+                    return quote! {
+                        compile_error!(#msg);
+                        #ident: ()
+                    };
+                }
+                let bool_lit = if is_true { "true" } else { "false" };
+                quote! {
+                    #[clap(long=#name_lit, #short_attr num_args=0..=1, require_equals=true, default_missing_value="true", default_value=#bool_lit, #help_attr)]
+                    #ident: Option<bool>
+                    #negation
+                }
+            } else {
+                // Not `SetTrue`: that defaults to false, masking env and config.
+                // `--flag` means true; `--flag=false` overrides a true default or config value.
+                quote! {
+                    #[clap(long=#name_lit, #short_attr num_args=0..=1, require_equals=true, default_missing_value="true", #help_attr)]
+                    #ident: Option<bool>
+                    #negation
+                }
+            }
+        } else {
+            let dv_attr = if let (true, Some(dv)) = (clap_default, &field.arg_attrs.default_value) {
+                let dv_lit = LitStr::new(dv, Span::call_site());
+                quote!(default_value=#dv_lit,)
+            } else {
+                quote!()
+            };
+            let is_vec = field.is_vec_type() || field.option_vec_inner_type().is_some();
+            let num_args = field.arg_attrs.num_args.map(|n| quote!(num_args = #n,));
+            let multi = match (field.arg_attrs.action.as_deref(), is_vec) {
+                // Explicit action: one value per occurrence unless num_args says otherwise
+                (Some("append"), _) => quote!(#num_args action = ::clap::ArgAction::Append,),
+                (Some(_), _) => quote!(#num_args action = ::clap::ArgAction::Set,),
+                (None, true) => match num_args {
+                    Some(n) => quote!(#n action = ::clap::ArgAction::Append,),
+                    None => quote!(num_args = 1.., action = ::clap::ArgAction::Append,),
+                },
+                (None, false) => quote!(),
+            };
+            let parser_attr = value_parser_attr(field);
+            let parser_attr = quote!(#parser_attr #value_names_attr);
+            // `Option<Vec<T>>` stays as is: clap's `None` is "flag not given"
+            let field_ty = match field.option_vec_inner_type() {
+                Some(inner) => quote!(Option<Vec<#inner>>),
+                None => {
+                    let t = &field.ty;
+                    quote!(Option<#t>)
+                }
+            };
+
+            quote! {
+                #[clap(long=#name_lit, #short_attr #dv_attr #multi #parser_attr #help_attr)]
+                #ident: #field_ty
+            }
+        }
+    }
+}
+/// Generate ephemeral config field if field is not cli_only
+fn generate_config_field(field: &FieldInfo) -> TokenStream2 {
+    let ident = &field.ident;
+    let ty = &field.ty;
+    if field.arg_attrs.rest {
+        return quote! {
+            #[serde(flatten)]
+            pub #ident: #ty
+        };
+    }
+
+    // Only rename when the key isn't the field name (explicit `name`, `config_case`)
+    let key = field.config_key();
+    let rename_attr = if *ident != key {
+        let name_lit = LitStr::new(&key, Span::call_site());
+        quote!(#[serde(rename = #name_lit)])
+    } else {
+        quote!()
+    };
+    // `server-port` and `server_port` alike
+    let aliases = field.config_key_aliases();
+    let rename_attr = quote!(#rename_attr #(#[serde(alias = #aliases)])*);
+
+    let has_default =
+        field.arg_attrs.default_value.is_some() || field.arg_attrs.default_value_t.is_some();
+    let default_attr = match has_default {
+        true => {
+            let fn_name = LitStr::new(&default_fn_ident(field).to_string(), Span::call_site());
+            quote!(default = #fn_name)
+        }
+        false => quote!(default),
+    };
+    let serde_default = if field.arg_attrs.value_parser.is_some() {
+        let fn_name = LitStr::new(&deserialize_fn_ident(field).to_string(), Span::call_site());
+        quote!(#[serde(#default_attr, deserialize_with = #fn_name)])
+    } else if field.is_duration_type() {
+        let module = duration_mod(field);
+        let function = if field.option_inner_type().is_some() {
+            "deserialize_option"
+        } else if field.is_vec_type() {
+            "deserialize_vec"
+        } else {
+            "deserialize"
+        };
+        let path = LitStr::new(
+            &format!("{}::{}", module, function).replace(' ', ""),
+            Span::call_site(),
+        );
+        quote!(#[serde(#default_attr, deserialize_with = #path)])
+    } else {
+        quote!(#[serde(#default_attr)])
+    };
+
+    quote! {
+        #rename_attr
+        #serde_default
+        pub #ident: #ty
+    }
+}
+
+/// Look up the field's env var candidates => expression typed like the CLI field
+/// (`Option<FieldTy>`, or `FieldTy` for `Option<Vec<T>>`), returning early with
+/// `ClapConfigError::Invalid` if the value doesn't parse.
+fn env_lookup(field: &FieldInfo) -> Option<TokenStream2> {
+    if field.arg_attrs.env.is_empty() {
+        return None;
+    }
+    let names = &field.arg_attrs.env;
+    let parse = parse_str_expr(field, quote!(v), quote!(format!("env {}", k)));
+    let flatten = field.option_vec_inner_type().map(|_| quote!(.flatten()));
+    Some(quote! {
+        [#(#names),*]
+            .iter()
+            .find_map(|k| __inline_env_var(k).map(|v| (*k, v)))
+            .map(|(k, v)| #parse)
+            .transpose()
+            .map_err(::clap_config_file::ClapConfigError::Invalid)?
+            #flatten
+    })
+}
+
+/// Expression parsing the string `src` into the field's type => `Result<FieldTy, String>`,
+/// the error naming `origin` (e.g. the env var).
+fn parse_str_expr(field: &FieldInfo, src: TokenStream2, origin: TokenStream2) -> TokenStream2 {
+    let parse_one = |ty: &syn::Type| match &field.arg_attrs.value_parser {
+        Some(parser) => quote!(#parser(__src.trim()).map_err(|e| e.to_string())),
+        // `DEBUG=1`, `DEBUG=yes`: the spellings config files accept, not just true/false
+        None if is_bool(ty) => quote!(::clap_config_file::boolish::parse_bool(__src)),
+        None if field.is_duration_type() => {
+            let module = duration_mod(field);
+            quote!(#module::parse_duration(__src))
+        }
+        // Same spellings as on the command line (ValueEnum types needn't be FromStr)
+        None if field.arg_attrs.value_enum => {
+            quote!(<#ty as ::clap::ValueEnum>::from_str(__src.trim(), true))
+        }
+        None => quote!(__src.trim().parse::<#ty>().map_err(|e| e.to_string())),
+    };
+    let parse = if let Some(inner) = field.vec_inner_type() {
+        let one = parse_one(inner);
+        quote!(__src.split(',').map(|__src| #one).collect::<Result<_, String>>())
+    } else if let Some(inner) = field.option_vec_inner_type() {
+        let one = parse_one(inner);
+        quote!(__src.split(',').map(|__src| #one).collect::<Result<_, String>>().map(Some))
+    } else if let Some(inner) = field.option_inner_type() {
+        let one = parse_one(inner);
+        quote!(#one.map(Some))
+    } else {
+        parse_one(&field.ty)
+    };
+    quote!({
+        let __src: &str = &#src;
+        #parse.map_err(|e| format!("invalid value {:?} in {}: {}", __src, #origin, e))
+    })
+}
+
+fn is_bool(ty: &syn::Type) -> bool {
+    matches!(ty, syn::Type::Path(tp) if tp.path.is_ident("bool"))
+}
+
+/// Name of the generated serde `deserialize_with` fn for a `value_parser` field
+fn deserialize_fn_ident(field: &FieldInfo) -> syn::Ident {
+    syn::Ident::new(&format!("__deserialize_{}", field.ident), Span::call_site())
+}
+
+/// `deserialize_with` fns running each `value_parser` over the config file's string
+/// form of the value (each element, for `Vec`), so config is validated like the CLI.
+fn generate_deserialize_fns(fields: &[&FieldInfo]) -> TokenStream2 {
+    let fns = fields.iter().filter_map(|f| {
+        let parser = f.arg_attrs.value_parser.as_ref()?;
+        let fn_ident = deserialize_fn_ident(f);
+        let ty = &f.ty;
+        let body = if f.is_vec_type() {
+            quote! {
+                let raw: Vec<String> = ::serde::Deserialize::deserialize(d)?;
+                raw.iter()
+                    .map(|s| #parser(s).map_err(::serde::de::Error::custom))
+                    .collect()
+            }
+        } else if f.option_vec_inner_type().is_some() {
+            quote! {
+                let raw: Option<Vec<String>> = ::serde::Deserialize::deserialize(d)?;
+                raw.map(|raw| {
+                    raw.iter()
+                        .map(|s| #parser(s).map_err(::serde::de::Error::custom))
+                        .collect()
+                })
+                .transpose()
+            }
+        } else if f.option_inner_type().is_some() {
+            quote! {
+                let raw: Option<String> = ::serde::Deserialize::deserialize(d)?;
+                raw.map(|s| #parser(&s)).transpose().map_err(::serde::de::Error::custom)
+            }
+        } else {
+            quote! {
+                let raw: String = ::serde::Deserialize::deserialize(d)?;
+                #parser(&raw).map_err(::serde::de::Error::custom)
+            }
+        };
+        Some(quote! {
+            fn #fn_ident<'de, D: ::serde::Deserializer<'de>>(d: D) -> Result<#ty, D::Error> {
+                #body
+            }
+        })
+    });
+    quote!(#(#fns)*)
+}
+
+/// Name of the generated fn returning a field's declared default_value
+fn default_fn_ident(field: &FieldInfo) -> syn::Ident {
+    syn::Ident::new(&format!("__default_{}", field.ident), Span::call_site())
+}
+
+/// `fn __default_<field>() -> T` for every config field with a default_value (or
+/// default_value_t), so
+/// serde (field absent from config) and the ephemeral config's Default agree
+/// with the declared default.
+fn generate_default_fns(fields: &[&FieldInfo]) -> TokenStream2 {
+    let fns = fields.iter().filter_map(|f| {
+        let fn_ident = default_fn_ident(f);
+        let ty = &f.ty;
+        if let Some(expr) = &f.arg_attrs.default_value_t {
+            return Some(quote! {
+                fn #fn_ident() -> #ty {
+                    #expr
+                }
+            });
+        }
+        let dv = f.arg_attrs.default_value.as_ref()?;
+        let parse = parse_str_expr(f, quote!(#dv), quote!("default_value"));
+        Some(quote! {
+            fn #fn_ident() -> #ty {
+                // A bad default_value is a bug in the struct, not a user error
+                #parse.unwrap_or_else(|e| panic!("{}", e))
+            }
+        })
+    });
+    quote!(#(#fns)*)
+}
+
+/// Map fields: config entries, then `KEY=VALUE,...` from env, then CLI pairs, each
+/// inserted on top of the last (later wins per key)
+fn unify_map_field(field: &FieldInfo) -> Option<TokenStream2> {
+    let (key_ty, val_ty) = field.map_types()?;
+    let ident = &field.ident;
+    let flag = format!("--{}", field.cli_name());
+    let has_env = !field.arg_attrs.env.is_empty();
+    let base = match field.arg_attrs.availability {
+        FieldAvailability::CliAndConfig => quote!(ephemeral_cfg.#ident.clone()),
+        FieldAvailability::CliOnly => quote!(Default::default()),
+        FieldAvailability::ConfigOnly if has_env => quote!(ephemeral_cfg.#ident.clone()),
+        _ => return None,
+    };
+    let env = has_env.then(|| {
+        let names = &field.arg_attrs.env;
+        quote! {
+            if let Some((name, raw)) = [#(#names),*]
+                .iter()
+                .find_map(|k| __inline_env_var(k).map(|v| (*k, v)))
+            {
+                for kv in raw.split(',').filter(|kv| !kv.trim().is_empty()) {
+                    let (k, v) = kv.split_once('=').ok_or_else(|| {
+                        ::clap_config_file::ClapConfigError::Invalid(format!(
+                            "env {} expects KEY=VALUE[,KEY=VALUE...], got {:?}",
+                            name, raw
+                        ))
+                    })?;
+                    let parse_err = |e: &dyn ::std::fmt::Display| {
+                        ::clap_config_file::ClapConfigError::Invalid(format!(
+                            "invalid value in env {}: {}",
+                            name, e
+                        ))
+                    };
+                    let k = k.trim().parse::<#key_ty>().map_err(|e| parse_err(&e))?;
+                    let v = v.trim().parse::<#val_ty>().map_err(|e| parse_err(&e))?;
+                    merged.insert(k, v);
+                }
+            }
+        }
+    });
+    if field.arg_attrs.availability == FieldAvailability::ConfigOnly {
+        return Some(quote! {
+            {
+                let mut merged = #base;
+                #env
+                merged
+            }
+        });
+    }
+    let pairs = if field.arg_attrs.num_args.unwrap_or(1) == 1 {
+        quote! {
+            raw.iter().map(|kv| match kv.split_once('=') {
+                Some((k, v)) => Ok((k.to_string(), v.to_string())),
+                None => Err(::clap_config_file::ClapConfigError::Invalid(format!(
+                    "{} expects KEY=VALUE, got {:?}",
+                    #flag, kv
+                ))),
+            }).collect::<Result<Vec<_>, _>>()?
+        }
+    } else {
+        quote! {
+            raw.chunks_exact(2).map(|kv| (kv[0].clone(), kv[1].clone())).collect::<Vec<_>>()
+        }
+    };
+    Some(quote! {
+        {
+            let mut merged = #base;
+            #env
+            if let Some(raw) = cli.#ident {
+                for (k, v) in #pairs {
+                    let parse_err = |e: &dyn ::std::fmt::Display| {
+                        ::clap_config_file::ClapConfigError::Invalid(format!(
+                            "invalid value for {}: {}",
+                            #flag, e
+                        ))
+                    };
+                    let k = k.parse::<#key_ty>().map_err(|e| parse_err(&e))?;
+                    let v = v.parse::<#val_ty>().map_err(|e| parse_err(&e))?;
+                    merged.insert(k, v);
+                }
+            }
+            merged
+        }
+    })
+}
+
+/// `member: value` for the final struct: the merged value, then `trim`, then `map`.
+fn unify_field(field: &FieldInfo) -> TokenStream2 {
+    let member = &field.member;
+    let mut value = unify_value(field);
+    if field.arg_attrs.trim {
+        let trim = if field.is_vec_type() {
+            quote! {
+                for v in value.iter_mut() {
+                    *v = v.trim().to_string();
+                }
+            }
+        } else if field.option_vec_inner_type().is_some() {
+            quote! {
+                for v in value.iter_mut().flatten() {
+                    *v = v.trim().to_string();
+                }
+            }
+        } else if field.option_inner_type().is_some() {
+            quote! {
+                if let Some(v) = value.as_mut() {
+                    *v = v.trim().to_string();
+                }
+            }
+        } else {
+            quote!(value = value.trim().to_string();)
+        };
+        value = quote!({
+            let mut value = #value;
+            #trim
+            value
+        });
+    }
+    if let Some(map) = &field.arg_attrs.map {
+        value = quote!((#map)(#value));
+    }
+    quote!(#member: #value)
+}
+
+/// The ephemeral CLI value of a field as an `Option`. A `count` flag is a plain
+/// `u8` there (clap counts from 0), so zero occurrences reads as unset.
+fn cli_value(field: &FieldInfo) -> TokenStream2 {
+    let ident = &field.ident;
+    if field.arg_attrs.count {
+        let ty = &field.ty;
+        quote!((cli.#ident > 0).then(|| <#ty>::from(cli.#ident)))
+    } else if field.arg_attrs.negatable {
+        // The two conflict, so at most one was given (a clap default only fills the flag)
+        let no_ident = negation_ident(field);
+        quote!((if cli.#no_ident { Some(false) } else { cli.#ident }))
+    } else {
+        quote!(cli.#ident)
+    }
+}
+
+/// The ephemeral CLI field behind a `negatable` flag's `--no-{name}`.
+fn negation_ident(field: &FieldInfo) -> syn::Ident {
+    syn::Ident::new(&format!("__no_{}", field.ident), Span::call_site())
+}
+
+/// Merge ephemeral CLI + ephemeral config => final value
+/// Precedence: CLI > env > config > default.
+fn unify_value(field: &FieldInfo) -> TokenStream2 {
+    let ident = &field.ident;
+    let cli_value = cli_value(field);
+    let cli_val = match env_lookup(field) {
+        Some(env) => quote!((match #cli_value {
+            Some(v) => Some(v),
+            None => #env,
+        })),
+        None => cli_value,
+    };
+    if let Some(map_merge) = unify_map_field(field) {
+        return map_merge;
+    }
+    match field.arg_attrs.availability {
+        FieldAvailability::CliOnly => {
+            if let Some(expr) = &field.arg_attrs.default_value_t {
+                quote!(#cli_val.unwrap_or_else(|| #expr))
+            } else if field.option_vec_inner_type().is_some()
+                || (field.arg_attrs.positional && field.option_inner_type().is_some())
+            {
+                quote!(#cli_val)
+            } else if field.is_vec_type() {
+                quote!(#cli_val.unwrap_or_default())
+            } else if field.is_bool_type() {
+                quote!(#cli_val.unwrap_or(false))
+            } else {
+                quote!(#cli_val.unwrap_or_default())
+            }
+        }
+        FieldAvailability::ConfigOnly if !field.arg_attrs.flatten.is_empty() => {
+            let subs = flattened_cli_fields(field);
+            let overrides = field
+                .arg_attrs
+                .flatten
+                .iter()
+                .zip(&subs)
+                .map(|((sub, ty), f)| {
+                    let cli_ident = &f.ident;
+                    let value = if generic_arg(ty, "Option").is_some() {
+                        quote!(Some(v))
+                    } else {
+                        quote!(v)
+                    };
+                    quote! {
+                        if let Some(v) = cli.#cli_ident {
+                            nested.#sub = #value;
+                        }
+                    }
+                });
+            quote! {
+                {
+                    let mut nested = ephemeral_cfg.#ident;
+                    #(#overrides)*
+                    nested
+                }
+            }
+        }
+        FieldAvailability::ConfigOnly => match env_lookup(field) {
+            // No flag, but env still beats the file (keeps secrets out of both shell history and the repo)
+            Some(env) if field.option_vec_inner_type().is_some() => {
+                quote!(#env.or(ephemeral_cfg.#ident))
+            }
+            Some(env) => quote!(#env.unwrap_or_else(|| ephemeral_cfg.#ident)),
+            None => quote!(ephemeral_cfg.#ident),
+        },
+        FieldAvailability::CliAndConfig => {
+            if field.option_vec_inner_type().is_some() {
+                // `None` on either side is "not set", unlike an empty list
+                match field.arg_attrs.multi_value_behavior {
+                    MultiValueBehavior::Extend => quote! {
+                        match (ephemeral_cfg.#ident.clone(), #cli_val) {
+                            (Some(mut merged), Some(cli_vec)) => {
+                                merged.extend(cli_vec);
+                                Some(merged)
+                            }
+                            (merged, None) => merged,
+                            (None, cli_vec) => cli_vec,
+                        }
+                    },
+                    MultiValueBehavior::ExtendUnique => quote! {
+                        match (ephemeral_cfg.#ident.clone(), #cli_val) {
+                            (Some(mut merged), Some(cli_vec)) => {
+                                merged.extend(cli_vec);
+                                Some(merged)
+                            }
+                            (merged, None) => merged,
+                            (None, cli_vec) => cli_vec,
+                        }
+                        .map(|mut merged| {
+                            let mut seen = ::std::collections::HashSet::new();
+                            merged.retain(|v| seen.insert(v.clone()));
+                            merged
+                        })
+                    },
+                    MultiValueBehavior::Overwrite => quote! {
+                        #cli_val.or_else(|| ephemeral_cfg.#ident.clone())
+                    },
+                    // parse_fields keeps it to config-only fields
+                    MultiValueBehavior::MergeByKey => unreachable!(),
+                }
+            } else if field.is_vec_type() {
+                match field.arg_attrs.multi_value_behavior {
+                    MultiValueBehavior::Extend => quote! {
+                        {
+                            let mut merged = ephemeral_cfg.#ident.clone();
+                            if let Some(cli_vec) = #cli_val {
+                                merged.extend(cli_vec);
+                            }
+                            merged
+                        }
+                    },
+                    MultiValueBehavior::ExtendUnique => quote! {
+                        {
+                            let mut merged = ephemeral_cfg.#ident.clone();
+                            if let Some(cli_vec) = #cli_val {
+                                merged.extend(cli_vec);
+                            }
+                            // Order-preserving: keep each value's first occurrence
+                            let mut seen = ::std::collections::HashSet::new();
+                            merged.retain(|v| seen.insert(v.clone()));
+                            merged
+                        }
+                    },
+                    MultiValueBehavior::Overwrite => quote! {
+                        #cli_val.unwrap_or_else(|| ephemeral_cfg.#ident.clone())
+                    },
+                    MultiValueBehavior::MergeByKey => unreachable!(),
+                }
+            } else if field.is_bool_type() {
+                quote!(#cli_val.unwrap_or(ephemeral_cfg.#ident))
+            } else {
+                quote!(#cli_val.unwrap_or_else(|| ephemeral_cfg.#ident))
+            }
+        }
+        FieldAvailability::Internal => {
+            quote!(Default::default())
+        }
+    }
+}
+
+/// Implement Debug for final struct
+fn generate_debug_impl(
+    struct_ident: &syn::Ident,
+    generics: &syn::Generics,
+    fields: &[FieldInfo],
+) -> TokenStream2 {
+    let field_names = fields.iter().map(|fi| fi.ident.to_string());
+    let field_members = fields.iter().map(|fi| &fi.member);
+    quote! {
+        impl #generics ::std::fmt::Debug for #struct_ident #generics {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                let mut dbg = f.debug_struct(stringify!(#struct_ident));
+                #( dbg.field(#field_names, &self.#field_members); )*
+                dbg.finish()
+            }
+        }
+    }
+}
+
+/// Implement Serialize for final struct.
+/// Internal (no `#[config_arg]`) fields are skipped unless `include_internal`.
+/// A `rest` field's entries are written inline, as a map, so they round-trip.
+fn generate_serialize_impl(
+    struct_ident: &syn::Ident,
+    generics: &syn::Generics,
+    fields: &[FieldInfo],
+    include_internal: bool,
+) -> TokenStream2 {
+    let rest = fields
+        .iter()
+        .find(|fi| fi.arg_attrs.rest)
+        .map(|fi| &fi.member);
+    let fields: Vec<&FieldInfo> = fields
+        .iter()
+        .filter(|fi| include_internal || fi.arg_attrs.availability != FieldAvailability::Internal)
+        .filter(|fi| !fi.arg_attrs.rest)
+        .collect();
+    let field_members = fields.iter().map(|fi| &fi.member);
+    let field_names = fields.iter().map(|fi| fi.ident.to_string());
+    let num_fields = fields.len();
+
+    if let Some(rest) = rest {
+        return quote! {
+            impl #generics ::serde::Serialize for #struct_ident #generics {
+                fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                where
+                    S: ::serde::Serializer
+                {
+                    use ::serde::ser::SerializeMap;
+                    let mut map = serializer.serialize_map(None)?;
+                    #(
+                        map.serialize_entry(#field_names, &self.#field_members)?;
+                    )*
+                    for (k, v) in &self.#rest {
+                        map.serialize_entry(k, v)?;
+                    }
+                    map.end()
+                }
+            }
+        };
+    }
+
+    quote! {
+        impl #generics ::serde::Serialize for #struct_ident #generics {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: ::serde::Serializer
+            {
+                use ::serde::ser::SerializeStruct;
+                let mut st = serializer.serialize_struct(
+                    stringify!(#struct_ident),
+                    #num_fields
+                )?;
+                #(
+                    st.serialize_field(#field_names, &self.#field_members)?;
+                )*
+                st.end()
+            }
+        }
+    }
+}
+
+/// Implement PartialEq + Eq + Hash for final struct (every field must support them)
+fn generate_hash_eq_impl(
+    struct_ident: &syn::Ident,
+    generics: &syn::Generics,
+    fields: &[FieldInfo],
+) -> TokenStream2 {
+    let eq_members = fields.iter().map(|fi| &fi.member);
+    let hash_members = fields.iter().map(|fi| &fi.member);
+    quote! {
+        impl #generics ::std::cmp::PartialEq for #struct_ident #generics {
+            fn eq(&self, other: &Self) -> bool {
+                true #( && self.#eq_members == other.#eq_members )*
+            }
+        }
+
+        impl #generics ::std::cmp::Eq for #struct_ident #generics {}
+
+        impl #generics ::std::hash::Hash for #struct_ident #generics {
+            fn hash<H: ::std::hash::Hasher>(&self, state: &mut H) {
+                #( ::std::hash::Hash::hash(&self.#hash_members, state); )*
+            }
+        }
+    }
+}
+
+/// `pub fn {field}(&self)` per field: `Copy` primitives by value, `String` as
+/// `&str`, anything else by reference.
+fn generate_getters_impl(
+    struct_ident: &syn::Ident,
+    generics: &syn::Generics,
+    fields: &[FieldInfo],
+) -> TokenStream2 {
+    let getters = fields.iter().map(|fi| {
+        let ident = &fi.ident;
+        let member = &fi.member;
+        let ty = &fi.ty;
+        let doc = format!("The `{}` field.", fi.ident);
+        if is_copy_primitive(ty) {
+            quote! {
+                #[doc = #doc]
+                pub fn #ident(&self) -> #ty { self.#member }
+            }
+        } else if matches!(ty, syn::Type::Path(tp) if tp.path.is_ident("String")) {
+            quote! {
+                #[doc = #doc]
+                pub fn #ident(&self) -> &str { &self.#member }
+            }
+        } else {
+            quote! {
+                #[doc = #doc]
+                pub fn #ident(&self) -> &#ty { &self.#member }
+            }
+        }
+    });
+    quote! {
+        impl #generics #struct_ident #generics {
+            #( #getters )*
+        }
+    }
+}
+
+fn is_copy_primitive(ty: &syn::Type) -> bool {
+    const PRIMITIVES: &[&str] = &[
+        "bool", "char", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64",
+        "i128", "isize", "f32", "f64",
+    ];
+    matches!(ty, syn::Type::Path(tp)
+        if tp.qself.is_none() && PRIMITIVES.iter().any(|p| tp.path.is_ident(p)))
+}
+
+/// Implement FromStr for a single-field struct by parsing that field
+fn generate_from_str_impl(
+    struct_ident: &syn::Ident,
+    generics: &syn::Generics,
+    fields: &[FieldInfo],
+) -> syn::Result<TokenStream2> {
+    let field = match fields {
+        [field] => field,
+        _ => {
+            return Err(Error::new_spanned(
+                struct_ident,
+                "#[config_from_str] requires a struct with exactly one field.",
+            ))
+        }
+    };
+    let member = &field.member;
+    let ty = &field.ty;
+
+    Ok(quote! {
+        impl #generics ::std::str::FromStr for #struct_ident #generics {
+            type Err = <#ty as ::std::str::FromStr>::Err;
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Ok(Self { #member: s.parse()? })
+            }
+        }
+    })
+}
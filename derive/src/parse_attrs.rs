@@ -0,0 +1,1490 @@
+use heck::{ToKebabCase, ToSnakeCase};
+use syn::{spanned::Spanned, Attribute, Lit, Meta, MetaNameValue};
+
+/// Names accepted by `#[config_file_formats]`; `clap_config_file::format::file_format`
+/// maps each to a `config::FileFormat`.
+const KNOWN_FORMATS: &[&str] = &[
+    "yaml", "yml", "json", "jsonc", "json5", "toml", "ini", "ron",
+];
+
+/// For struct-level
+#[derive(Debug, Default)]
+pub struct MacroConfig {
+    /// `#[config_file_name = "myapp,.myapprc"]`: tried in order within each directory.
+    pub base_name: Vec<String>,
+    pub formats: Vec<String>,
+    /// `#[config_file_format_precedence]`: on same-dir ambiguity the first
+    /// declared format wins instead of erroring.
+    pub format_precedence: bool,
+    /// `#[config_from_str]`: generate `FromStr` (single-field structs only).
+    pub from_str: bool,
+    /// `#[config_file_cargo_metadata = "mytool"]`: instead of discovering
+    /// `<base_name>.<ext>`, read `[workspace.metadata.mytool]` and
+    /// `[package.metadata.mytool]` from the nearest `Cargo.toml`.
+    pub cargo_metadata: Option<String>,
+    /// `#[config_file_recursive = "config"]`: instead of walking up, collect every
+    /// declared-format file below this directory (relative to the cwd).
+    pub recursive_root: Option<String>,
+    /// `#[config_file_cli_file_mode = "layer"]`: explicit `--config-file`s are
+    /// layered over the discovered config instead of replacing it ("replace", default).
+    pub cli_file_layer: bool,
+    /// `#[config_file_nonempty]`: an empty or whitespace-only config file is an error.
+    pub nonempty: bool,
+    /// `#[config_serialize_internal]`: include fields without `#[config_arg]` in `Serialize`.
+    pub serialize_internal: bool,
+    /// `#[config_hash_eq]`: generate `PartialEq`, `Eq` and `Hash` over all fields.
+    pub hash_eq: bool,
+    /// `#[config_getters]`: generate a `pub fn {field}(&self)` accessor per field.
+    pub getters: bool,
+    /// `#[config_auto_short]`: flags without a `short` get the first free letter of
+    /// their kebab name.
+    pub auto_short: bool,
+    /// `#[config_validate]`: `parse`, `parse_from` and `try_parse_from` call the
+    /// struct's `ValidateConfig::validate` on the merged config.
+    pub validate: bool,
+    /// `#[config_file_merge = "layered"]`: merge every config file found walking up
+    /// (nearest wins) instead of erroring on more than one ("strict", default).
+    pub layered: bool,
+    /// `#[config_file_env_ignore_empty]`: `FOO=` counts as unset for env fallbacks.
+    pub env_ignore_empty: bool,
+    /// `#[config_file_require_explicit]`: no discovery; one of `--config-file`,
+    /// `--config` or `--no-config` must be given.
+    pub require_explicit: bool,
+    /// `#[config_file_search = "cwd,xdg,home"]`: location groups tried in order,
+    /// first with a config file wins. Empty means `cwd` only.
+    pub search: Vec<String>,
+    /// `#[config_file_examples = "my-tool --port 8080"]` (repeatable, one example per
+    /// line): listed under "Examples:" at the end of `--help`.
+    pub examples: Vec<String>,
+    /// `#[config_file_stop_at = ".git,.hg"]`: walking up ends at the first directory
+    /// holding one of these entries (searched itself). Empty means up to the root.
+    pub stop_at: Vec<String>,
+    /// `#[config_file_wrap_key = "self"]`: config files nest their keys under this
+    /// top-level key (`"self"` resolves to the base name).
+    pub wrap_key: Option<String>,
+    /// `#[config_case = "kebab"]`: config keys default to `server-port` instead of the
+    /// field name (`server_port`). Either spelling is accepted regardless.
+    pub kebab_keys: bool,
+    /// `#[config_file_include]`: a top-level `include` key in a config file names
+    /// files loaded below it. The key is reserved for that, so no field may use it.
+    pub include: bool,
+    /// `#[config_deny_unknown]`: a config key no field reads is a deserialization
+    /// error. Has no effect when a `rest` field collects such keys.
+    pub deny_unknown: bool,
+    /// `#[config_on_error = "warn"]`: what a config that fails to parse or deserialize does.
+    pub on_error: ConfigOnError,
+    /// `base_name` is an absolute file path: load it directly instead of discovering.
+    pub fixed_path: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ConfigOnError {
+    /// Return `ConfigDeserializeError` (the default)
+    #[default]
+    Fail,
+    /// Print the error to stderr, then carry on with every config field at its default
+    Warn,
+    /// Carry on with every config field at its default, silently
+    Default,
+}
+
+/// Field-level
+#[derive(Debug, Default, Clone)]
+pub struct ArgAttributes {
+    pub name: Option<String>,
+    pub short: Option<char>,
+    pub default_value: Option<String>,
+    /// `default_value_t = expr`: a typed default, evaluated where `default_value`
+    /// would be parsed.
+    pub default_value_t: Option<syn::Expr>,
+    pub positional: bool,
+    /// clap's 1-based positional index (`positional, index = 1`).
+    pub index: Option<usize>,
+    /// `trailing`: a positional `Vec` that takes everything after `--` (clap's
+    /// `last = true`). Implies `positional`.
+    pub trailing: bool,
+    pub availability: FieldAvailability,
+    pub multi_value_behavior: MultiValueBehavior,
+    /// Collected doc-comments (joined into one help string).
+    pub help_text: String,
+    /// Every doc paragraph, when there's more than the one in `help_text` (`--help` vs `-h`).
+    pub long_help: Option<String>,
+    /// `help_from = path::to::CONST`: help taken from a `&str` expression (e.g. for i18n).
+    pub help_from: Option<syn::Expr>,
+    /// `map = path::to::fn`: `fn(T) -> T` applied to the merged value, whatever its source.
+    pub map: Option<syn::Expr>,
+    /// `value_parser = path::to::fn`: `fn(&str) -> Result<T, E>` used for the CLI,
+    /// config (per element for `Vec`) and env alike.
+    pub value_parser: Option<syn::Path>,
+    /// Parse as a `Duration` ("90s", "1h30m") even if the type isn't spelled `Duration`.
+    pub duration: bool,
+    /// `parse_with = "humantime"`: a `Duration` in humantime's grammar (the
+    /// `humantime` feature) instead of the built-in one. Implies `duration`.
+    pub humantime: bool,
+    /// Trim surrounding whitespace from the final `String` (or `Option`/`Vec` of them).
+    pub trim: bool,
+    /// Explicit clap action ("append" or "set"), overriding the one inferred from the type.
+    pub action: Option<String>,
+    /// Values per occurrence, e.g. `num_args = 2` for `--define KEY VALUE`.
+    pub num_args: Option<usize>,
+    /// Help placeholders, e.g. `value_names = ["KEY", "VALUE"]`.
+    pub value_names: Vec<String>,
+    /// `alias = "db-url"` (repeatable): extra CLI flag names, also accepted as config keys.
+    pub aliases: Vec<String>,
+    /// `visible_alias = "server-port"` (repeatable): like `alias`, but listed in `--help`.
+    /// Also pushed onto `aliases`.
+    pub visible_aliases: Vec<String>,
+    /// Env var candidates, tried in order; the first one set wins.
+    pub env: Vec<String>,
+    /// All-or-nothing group name: every field sharing it must be set, or none.
+    pub mutually_required_with: Option<String>,
+    /// `requires_if = ("mode", "remote", "url")`: if field `mode` ends up as `remote`
+    /// (from any source), field `url` must be provided. Repeatable.
+    pub requires_if: Vec<(String, String, String)>,
+    /// `flatten(nesting_level = "u32", ...)`: the nested struct's listed fields get
+    /// dotted flags (`--extra-settings.nesting-level`) overriding the config value.
+    pub flatten: Vec<(syn::Ident, syn::Type)>,
+    /// `conflicts_with = "json-output"` (repeatable, or comma-separated): clap rejects
+    /// both flags together. Written as kebab flag names, stored as the fields' idents.
+    pub conflicts_with: Vec<String>,
+    /// `requires = "tls-cert"` (repeatable, or comma-separated): given on the CLI, this
+    /// flag needs the other one there too. Stored like `conflicts_with`.
+    pub requires: Vec<String>,
+    /// Given on the CLI, this flag makes the run ignore config files, as `--no-config` does.
+    pub exclusive_with_config: bool,
+    /// Must be given by the CLI, env or config; a missing value is an error, not `Default`.
+    pub required: bool,
+    /// `deprecated = "use --new-flag instead"`: still works, but warns when used.
+    pub deprecated: Option<String>,
+    /// Masked in `--dump-env` output unless `--unsafe` is given.
+    pub sensitive: bool,
+    /// `hide`: parsed as usual but left out of `--help`.
+    pub hide: bool,
+    /// From the struct's `#[config_case = "kebab"]`: `config_key` is kebab-case.
+    pub kebab_key: bool,
+    /// `count`: an unsigned integer counting occurrences (`-vvv` is 3).
+    pub count: bool,
+    /// `negatable`: a `bool` flag also gets `--no-{name}`, which sets it to false.
+    pub negatable: bool,
+    /// `rest`: a `HashMap<String, serde_json::Value>` collecting the config keys
+    /// no other field claims (serde's `flatten`). Config-only.
+    pub rest: bool,
+    /// Only `--flag=value` is accepted, so `--flag value` can't swallow a positional.
+    pub require_equals: bool,
+    /// The type (or its `Option`/`Vec` element) derives clap's `ValueEnum`.
+    pub value_enum: bool,
+    /// `key = "name"`: the element field `merge_by_key` matches list entries on.
+    pub merge_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum FieldAvailability {
+    #[default]
+    Internal,
+    CliOnly,
+    ConfigOnly,
+    CliAndConfig,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub enum MultiValueBehavior {
+    #[default]
+    Extend,
+    /// `"extend-unique"`: extend, dropping repeats (first occurrence kept)
+    ExtendUnique,
+    Overwrite,
+    /// `"merge_by_key"` + `key = "name"`: config-only lists of tables, upserted
+    /// across config sources by that key
+    MergeByKey,
+}
+
+/// Info about each field
+#[derive(Debug, Clone)]
+pub struct FieldInfo {
+    pub ident: syn::Ident,
+    /// How the field is accessed on the final struct: the ident, or `0` for a newtype.
+    pub member: syn::Member,
+    pub ty: syn::Type,
+    pub arg_attrs: ArgAttributes,
+}
+impl FieldInfo {
+    /// Long flag name: explicit `name` or the kebab-cased ident.
+    pub fn cli_name(&self) -> String {
+        self.arg_attrs
+            .name
+            .clone()
+            .unwrap_or_else(|| self.ident.to_string().to_kebab_case())
+    }
+    /// Key in the config file: explicit `name`, or the ident as-is (kebab-case under
+    /// `#[config_case = "kebab"]`).
+    pub fn config_key(&self) -> String {
+        match &self.arg_attrs.name {
+            Some(name) => name.clone(),
+            None if self.arg_attrs.kebab_key => self.ident.to_string().to_kebab_case(),
+            None => self.ident.to_string(),
+        }
+    }
+    /// Other config keys accepted for this field: its `alias`es, then the
+    /// snake_case and kebab-case spellings of the key.
+    pub fn config_key_aliases(&self) -> Vec<String> {
+        let key = self.config_key();
+        let mut aliases = self.arg_attrs.aliases.clone();
+        for variant in [key.to_snake_case(), key.to_kebab_case()] {
+            if variant != key && !aliases.contains(&variant) {
+                aliases.push(variant);
+            }
+        }
+        aliases
+    }
+    // e.g. "bool" => is_bool_type
+    pub fn is_bool_type(&self) -> bool {
+        if let syn::Type::Path(tp) = &self.ty {
+            if let Some(seg) = tp.path.segments.last() {
+                return seg.ident == "bool";
+            }
+        }
+        false
+    }
+    // e.g. "std::time::Duration", "Option<Duration>", "Vec<Duration>" or
+    // #[config_arg(duration)] => is_duration_type
+    pub fn is_duration_type(&self) -> bool {
+        self.arg_attrs.duration || is_duration(duration_element(&self.ty))
+    }
+    // e.g. "Option<String>" => Some(String)
+    pub fn option_inner_type(&self) -> Option<&syn::Type> {
+        generic_arg(&self.ty, "Option")
+    }
+    // e.g. "Vec<u16>" => Some(u16)
+    pub fn vec_inner_type(&self) -> Option<&syn::Type> {
+        generic_arg(&self.ty, "Vec")
+    }
+    // e.g. "Option<Vec<String>>" => Some(String)
+    pub fn option_vec_inner_type(&self) -> Option<&syn::Type> {
+        generic_arg(self.option_inner_type()?, "Vec")
+    }
+    // e.g. "HashMap<String, String>" => Some((String, String))
+    pub fn map_types(&self) -> Option<(&syn::Type, &syn::Type)> {
+        if let syn::Type::Path(tp) = &self.ty {
+            let seg = tp.path.segments.last()?;
+            if seg.ident != "HashMap" && seg.ident != "BTreeMap" {
+                return None;
+            }
+            if let syn::PathArguments::AngleBracketed(args) = &seg.arguments {
+                let mut types = args.args.iter().filter_map(|a| match a {
+                    syn::GenericArgument::Type(t) => Some(t),
+                    _ => None,
+                });
+                return Some((types.next()?, types.next()?));
+            }
+        }
+        None
+    }
+    // e.g. "Vec<String>" => is_vec_type
+    pub fn is_vec_type(&self) -> bool {
+        if let syn::Type::Path(tp) = &self.ty {
+            if let Some(seg) = tp.path.segments.last() {
+                return seg.ident == "Vec";
+            }
+        }
+        false
+    }
+}
+
+/// `T` of `Option<T>` or `Vec<T>`, else `ty` itself: what a Duration field parses.
+fn duration_element(ty: &syn::Type) -> &syn::Type {
+    generic_arg(ty, "Option")
+        .or_else(|| generic_arg(ty, "Vec"))
+        .unwrap_or(ty)
+}
+
+fn is_map(ty: &syn::Type) -> bool {
+    matches!(ty, syn::Type::Path(tp) if tp.path.segments.last()
+        .is_some_and(|seg| seg.ident == "HashMap" || seg.ident == "BTreeMap"))
+}
+
+fn is_duration(ty: &syn::Type) -> bool {
+    matches!(ty, syn::Type::Path(tp) if tp.path.segments.last().is_some_and(|seg| seg.ident == "Duration"))
+}
+
+/// The single generic argument of `wrapper<T>`, if `ty` is one.
+pub fn generic_arg<'a>(ty: &'a syn::Type, wrapper: &str) -> Option<&'a syn::Type> {
+    if let syn::Type::Path(tp) = ty {
+        let seg = tp.path.segments.last()?;
+        if seg.ident != wrapper {
+            return None;
+        }
+        if let syn::PathArguments::AngleBracketed(args) = &seg.arguments {
+            if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
+                return Some(inner);
+            }
+        }
+    }
+    None
+}
+
+/// Parse a `default_value` literal as the field's primitive type (or the element
+/// type of an `Option`/`Vec`, comma-separated), so a typo fails the build rather
+/// than panicking at startup. Other types are parsed at runtime.
+fn check_default_value(ty: &syn::Type, dv: &str) -> Result<(), String> {
+    let (ty, values): (_, Vec<&str>) = match generic_arg(ty, "Vec") {
+        Some(inner) => (inner, dv.split(',').map(str::trim).collect()),
+        None => (generic_arg(ty, "Option").unwrap_or(ty), vec![dv]),
+    };
+    let syn::Type::Path(tp) = ty else {
+        return Ok(());
+    };
+    let Some(name) = tp.path.get_ident().map(|i| i.to_string()) else {
+        return Ok(());
+    };
+    for v in values {
+        let err = match name.as_str() {
+            "u8" => v.parse::<u8>().err().map(|e| e.to_string()),
+            "u16" => v.parse::<u16>().err().map(|e| e.to_string()),
+            "u32" => v.parse::<u32>().err().map(|e| e.to_string()),
+            "u64" => v.parse::<u64>().err().map(|e| e.to_string()),
+            "u128" => v.parse::<u128>().err().map(|e| e.to_string()),
+            "usize" => v.parse::<u64>().err().map(|e| e.to_string()),
+            "i8" => v.parse::<i8>().err().map(|e| e.to_string()),
+            "i16" => v.parse::<i16>().err().map(|e| e.to_string()),
+            "i32" => v.parse::<i32>().err().map(|e| e.to_string()),
+            "i64" => v.parse::<i64>().err().map(|e| e.to_string()),
+            "i128" => v.parse::<i128>().err().map(|e| e.to_string()),
+            "isize" => v.parse::<i64>().err().map(|e| e.to_string()),
+            "f32" => v.parse::<f32>().err().map(|e| e.to_string()),
+            "f64" => v.parse::<f64>().err().map(|e| e.to_string()),
+            "char" => v.parse::<char>().err().map(|e| e.to_string()),
+            _ => return Ok(()),
+        };
+        if let Some(e) = err {
+            return Err(format!(
+                "default_value {:?} is not a valid {}: {}",
+                v, name, e
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Parse struct-level: #[config_file_name(...)] / #[config_file_formats(...)]
+pub fn parse_struct_level_attrs(attrs: &[Attribute]) -> syn::Result<MacroConfig> {
+    let mut cfg = MacroConfig::default();
+
+    for attr in attrs {
+        if let Some(ident) = attr.path().get_ident() {
+            let name = ident.to_string();
+            if name == "config_file_name" {
+                // e.g. #[config_file_name="app-config"]
+                if let Meta::NameValue(MetaNameValue {
+                    value:
+                        syn::Expr::Lit(syn::ExprLit {
+                            lit: Lit::Str(s), ..
+                        }),
+                    ..
+                }) = attr.meta.clone()
+                {
+                    cfg.base_name = s
+                        .value()
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|n| !n.is_empty())
+                        .map(String::from)
+                        .collect();
+                }
+            } else if name == "config_file_formats" {
+                // e.g. #[config_file_formats="yaml,toml,json"]
+                if let Meta::NameValue(MetaNameValue {
+                    value:
+                        syn::Expr::Lit(syn::ExprLit {
+                            lit: Lit::Str(s), ..
+                        }),
+                    ..
+                }) = attr.meta.clone()
+                {
+                    let raw = s.value();
+                    // e.g. "yaml, toml, json" => ["yaml","toml","json"]
+                    cfg.formats = raw.split(',').map(|x| x.trim().to_string()).collect();
+                    if let Some(unknown) = cfg
+                        .formats
+                        .iter()
+                        .find(|f| !KNOWN_FORMATS.contains(&f.as_str()))
+                    {
+                        return Err(syn::Error::new(
+                            s.span(),
+                            format!(
+                                "Unknown config file format {:?} (expected one of: {})",
+                                unknown,
+                                KNOWN_FORMATS.join(", ")
+                            ),
+                        ));
+                    }
+                }
+            } else if name == "config_file_cargo_metadata" {
+                cfg.cargo_metadata = Some(str_value(attr)?);
+            } else if name == "config_file_format_precedence" {
+                attr.meta.require_path_only()?;
+                cfg.format_precedence = true;
+            } else if name == "config_file_wrap_key" {
+                cfg.wrap_key = Some(str_value(attr)?);
+            } else if name == "config_file_recursive" {
+                cfg.recursive_root = Some(str_value(attr)?);
+            } else if name == "config_on_error" {
+                cfg.on_error = match str_value(attr)?.as_str() {
+                    "fail" => ConfigOnError::Fail,
+                    "warn" => ConfigOnError::Warn,
+                    "default" => ConfigOnError::Default,
+                    other => {
+                        return Err(syn::Error::new(
+                            attr.span(),
+                            format!(
+                                "Invalid config_on_error: {} (expected \"fail\", \"warn\" or \"default\")",
+                                other
+                            ),
+                        ))
+                    }
+                };
+            } else if name == "config_case" {
+                cfg.kebab_keys = match str_value(attr)?.as_str() {
+                    "kebab" => true,
+                    "snake" => false,
+                    other => {
+                        return Err(syn::Error::new(
+                            attr.span(),
+                            format!(
+                                "Invalid config_case: {} (expected \"snake\" or \"kebab\")",
+                                other
+                            ),
+                        ))
+                    }
+                };
+            } else if name == "config_file_cli_file_mode" {
+                cfg.cli_file_layer = match str_value(attr)?.as_str() {
+                    "layer" => true,
+                    "replace" => false,
+                    other => {
+                        return Err(syn::Error::new(
+                            attr.span(),
+                            format!(
+                                "Invalid config_file_cli_file_mode: {} (expected \"layer\" or \"replace\")",
+                                other
+                            ),
+                        ))
+                    }
+                };
+            } else if name == "config_file_nonempty" {
+                attr.meta.require_path_only()?;
+                cfg.nonempty = true;
+            } else if name == "config_file_include" {
+                attr.meta.require_path_only()?;
+                cfg.include = true;
+            } else if name == "config_deny_unknown" {
+                attr.meta.require_path_only()?;
+                cfg.deny_unknown = true;
+            } else if name == "config_from_str" {
+                attr.meta.require_path_only()?;
+                cfg.from_str = true;
+            } else if name == "config_serialize_internal" {
+                attr.meta.require_path_only()?;
+                cfg.serialize_internal = true;
+            } else if name == "config_file_merge" {
+                cfg.layered = match str_value(attr)?.as_str() {
+                    "layered" => true,
+                    "strict" => false,
+                    other => {
+                        return Err(syn::Error::new(
+                            attr.span(),
+                            format!(
+                            "Invalid config_file_merge: {} (expected \"layered\" or \"strict\")",
+                            other
+                        ),
+                        ))
+                    }
+                };
+            } else if name == "config_file_env_ignore_empty" {
+                attr.meta.require_path_only()?;
+                cfg.env_ignore_empty = true;
+            } else if name == "config_file_require_explicit" {
+                attr.meta.require_path_only()?;
+                cfg.require_explicit = true;
+            } else if name == "config_file_search" {
+                let raw = str_value(attr)?;
+                for group in raw.split(',').map(|g| g.trim().to_string()) {
+                    if !["cwd", "xdg", "home"].contains(&group.as_str()) {
+                        return Err(syn::Error::new(
+                            attr.span(),
+                            format!(
+                                "Invalid config_file_search location: {:?} (expected cwd, xdg or home)",
+                                group
+                            ),
+                        ));
+                    }
+                    if cfg.search.contains(&group) {
+                        return Err(syn::Error::new(
+                            attr.span(),
+                            format!("config_file_search lists {:?} twice", group),
+                        ));
+                    }
+                    cfg.search.push(group);
+                }
+            } else if name == "config_file_examples" {
+                let raw = str_value(attr)?;
+                cfg.examples.extend(
+                    raw.lines()
+                        .map(str::trim)
+                        .filter(|l| !l.is_empty())
+                        .map(String::from),
+                );
+            } else if name == "config_file_stop_at" {
+                let raw = str_value(attr)?;
+                cfg.stop_at = raw
+                    .split(',')
+                    .map(|m| m.trim().to_string())
+                    .filter(|m| !m.is_empty())
+                    .collect();
+                if cfg.stop_at.is_empty() {
+                    return Err(syn::Error::new(
+                        attr.span(),
+                        "config_file_stop_at needs at least one marker name",
+                    ));
+                }
+            } else if name == "config_hash_eq" {
+                attr.meta.require_path_only()?;
+                cfg.hash_eq = true;
+            } else if name == "config_getters" {
+                attr.meta.require_path_only()?;
+                cfg.getters = true;
+            } else if name == "config_auto_short" {
+                attr.meta.require_path_only()?;
+                cfg.auto_short = true;
+            } else if name == "config_validate" {
+                attr.meta.require_path_only()?;
+                cfg.validate = true;
+            }
+        }
+    }
+
+    if cfg.cargo_metadata.is_some() && cfg.recursive_root.is_some() {
+        return Err(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "config_file_cargo_metadata and config_file_recursive are mutually exclusive",
+        ));
+    }
+
+    if !cfg.search.is_empty() && (cfg.cargo_metadata.is_some() || cfg.recursive_root.is_some()) {
+        return Err(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "config_file_search cannot be combined with config_file_cargo_metadata or config_file_recursive",
+        ));
+    }
+
+    if !cfg.stop_at.is_empty() && (cfg.cargo_metadata.is_some() || cfg.recursive_root.is_some()) {
+        return Err(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "config_file_stop_at bounds walking up, so it cannot be combined with config_file_cargo_metadata or config_file_recursive",
+        ));
+    }
+
+    if cfg.wrap_key.is_some() && cfg.cargo_metadata.is_some() {
+        return Err(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "config_file_wrap_key cannot be combined with config_file_cargo_metadata, which already reads a nested table",
+        ));
+    }
+
+    if cfg.base_name.is_empty() {
+        cfg.base_name = vec!["config".to_string()];
+    }
+    if cfg.formats.is_empty() {
+        cfg.formats = vec!["yaml".into()];
+    }
+
+    // `#[config_file_name = "/etc/myapp/config.yaml"]`: load exactly that file
+    let path = std::path::Path::new(&cfg.base_name[0]);
+    if cfg
+        .base_name
+        .iter()
+        .any(|n| std::path::Path::new(n).is_absolute())
+    {
+        if cfg.cargo_metadata.is_some()
+            || cfg.recursive_root.is_some()
+            || !cfg.search.is_empty()
+            || !cfg.stop_at.is_empty()
+            || cfg.base_name.len() > 1
+        {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "an absolute config_file_name cannot be combined with other names, config_file_cargo_metadata, config_file_recursive, config_file_search or config_file_stop_at",
+            ));
+        }
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        if !cfg.formats.iter().any(|f| f == ext) {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                format!(
+                    "absolute config_file_name {:?} must end in one of the config_file_formats ({})",
+                    cfg.base_name[0],
+                    cfg.formats.join(", ")
+                ),
+            ));
+        }
+        cfg.fixed_path = true;
+    }
+
+    if cfg.wrap_key.as_deref() == Some("self") {
+        // The file stem, for an absolute config_file_name
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or(&cfg.base_name[0]);
+        cfg.wrap_key = Some(stem.to_string());
+    }
+
+    Ok(cfg)
+}
+
+/// The string of a `#[name = "value"]` struct attribute
+fn str_value(attr: &Attribute) -> syn::Result<String> {
+    match &attr.meta {
+        Meta::NameValue(MetaNameValue {
+            value: syn::Expr::Lit(syn::ExprLit {
+                lit: Lit::Str(s), ..
+            }),
+            ..
+        }) => Ok(s.value()),
+        _ => Err(syn::Error::new(
+            attr.span(),
+            "expected a string value, e.g. #[attr = \"...\"]",
+        )),
+    }
+}
+
+/// Parse each field for #[config_arg(...)] plus doc comments
+/// `newtype_name` names the single field of a tuple struct (`struct Port(u16)` => `port`).
+pub fn parse_fields(
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    newtype_name: Option<syn::Ident>,
+) -> syn::Result<Vec<FieldInfo>> {
+    let mut out = Vec::new();
+    for f in fields {
+        let (ident, member) = match (&f.ident, &newtype_name) {
+            (Some(ident), _) => (ident.clone(), syn::Member::Named(ident.clone())),
+            (None, Some(name)) => (name.clone(), syn::Member::Unnamed(0.into())),
+            (None, None) => {
+                return Err(syn::Error::new(
+                    f.span(),
+                    "Unnamed field not supported by ClapConfigFile",
+                ))
+            }
+        };
+
+        let mut arg_attrs = ArgAttributes::default();
+        let mut has_config_arg = false;
+        let mut explicit_help: Option<String> = None;
+
+        for attr in &f.attrs {
+            if let Some(ident2) = attr.path().get_ident() {
+                if ident2 == "config_arg" {
+                    if has_config_arg {
+                        return Err(syn::Error::new_spanned(
+                            attr,
+                            "duplicate #[config_arg] on one field; combine them into a single #[config_arg(...)]",
+                        ));
+                    }
+                    has_config_arg = true;
+                    let meta_list = attr.meta.require_list()?;
+                    for nested in meta_list.parse_args_with(
+                        syn::punctuated::Punctuated::<Meta, syn::Token![,]>::parse_terminated,
+                    )? {
+                        match nested {
+                            Meta::NameValue(MetaNameValue { path, value, .. }) => {
+                                let key =
+                                    path.get_ident().map(|i| i.to_string()).unwrap_or_default();
+                                match (key.as_str(), value) {
+                                    (
+                                        "name" | "long",
+                                        syn::Expr::Lit(syn::ExprLit {
+                                            lit: Lit::Str(v), ..
+                                        }),
+                                    ) => {
+                                        arg_attrs.name = Some(v.value());
+                                    }
+                                    (
+                                        "short",
+                                        syn::Expr::Lit(syn::ExprLit {
+                                            lit: Lit::Char(v), ..
+                                        }),
+                                    ) => {
+                                        arg_attrs.short = Some(v.value());
+                                    }
+                                    (
+                                        "default_value",
+                                        syn::Expr::Lit(syn::ExprLit {
+                                            lit: Lit::Str(v), ..
+                                        }),
+                                    ) => {
+                                        arg_attrs.default_value = Some(v.value());
+                                    }
+                                    (
+                                        "accept_from",
+                                        syn::Expr::Lit(syn::ExprLit {
+                                            lit: Lit::Str(v), ..
+                                        }),
+                                    ) => {
+                                        if arg_attrs.positional {
+                                            return Err(syn::Error::new(
+                                                attr.span(),
+                                                "Positional arguments must be CLI-only. Remove accept_from attribute.",
+                                            ));
+                                        }
+                                        match v.value().as_str() {
+                                            "cli_only" => {
+                                                arg_attrs.availability = FieldAvailability::CliOnly
+                                            }
+                                            "config_only" => {
+                                                arg_attrs.availability =
+                                                    FieldAvailability::ConfigOnly
+                                            }
+                                            "cli_and_config" => {
+                                                arg_attrs.availability =
+                                                    FieldAvailability::CliAndConfig
+                                            }
+                                            other => {
+                                                return Err(syn::Error::new(
+                                                    attr.span(),
+                                                    format!("Invalid accept_from: {}", other),
+                                                ));
+                                            }
+                                        }
+                                    }
+                                    (
+                                        "parse_with",
+                                        syn::Expr::Lit(syn::ExprLit {
+                                            lit: Lit::Str(v), ..
+                                        }),
+                                    ) => {
+                                        if v.value() != "humantime" {
+                                            return Err(syn::Error::new(
+                                                v.span(),
+                                                "parse_with supports only \"humantime\"",
+                                            ));
+                                        }
+                                        arg_attrs.humantime = true;
+                                        arg_attrs.duration = true;
+                                    }
+                                    ("default_value_t", expr) => {
+                                        arg_attrs.default_value_t = Some(expr);
+                                    }
+                                    ("help_from", expr) => {
+                                        arg_attrs.help_from = Some(expr);
+                                    }
+                                    ("map", expr) => {
+                                        arg_attrs.map = Some(expr);
+                                    }
+                                    ("value_parser", syn::Expr::Path(p)) => {
+                                        arg_attrs.value_parser = Some(p.path);
+                                    }
+                                    (
+                                        "action",
+                                        syn::Expr::Lit(syn::ExprLit {
+                                            lit: Lit::Str(v), ..
+                                        }),
+                                    ) => match v.value().as_str() {
+                                        "append" | "set" => arg_attrs.action = Some(v.value()),
+                                        other => {
+                                            return Err(syn::Error::new(
+                                                v.span(),
+                                                format!(
+                                                    "Invalid action: {} (expected \"append\" or \"set\")",
+                                                    other
+                                                ),
+                                            ));
+                                        }
+                                    },
+                                    (
+                                        "index",
+                                        syn::Expr::Lit(syn::ExprLit {
+                                            lit: Lit::Int(v), ..
+                                        }),
+                                    ) => {
+                                        arg_attrs.index = Some(v.base10_parse()?);
+                                    }
+                                    (
+                                        "num_args",
+                                        syn::Expr::Lit(syn::ExprLit {
+                                            lit: Lit::Int(v), ..
+                                        }),
+                                    ) => {
+                                        arg_attrs.num_args = Some(v.base10_parse()?);
+                                    }
+                                    ("value_names", syn::Expr::Array(arr)) => {
+                                        for elem in arr.elems {
+                                            match elem {
+                                                syn::Expr::Lit(syn::ExprLit {
+                                                    lit: Lit::Str(v),
+                                                    ..
+                                                }) => arg_attrs.value_names.push(v.value()),
+                                                other => {
+                                                    return Err(syn::Error::new(
+                                                        other.span(),
+                                                        "value_names must be string literals",
+                                                    ))
+                                                }
+                                            }
+                                        }
+                                    }
+                                    (
+                                        "env",
+                                        syn::Expr::Lit(syn::ExprLit {
+                                            lit: Lit::Str(v), ..
+                                        }),
+                                    ) => {
+                                        // e.g. "DATABASE_URL,DB_URL" => ["DATABASE_URL","DB_URL"]
+                                        arg_attrs.env = v
+                                            .value()
+                                            .split(',')
+                                            .map(|x| x.trim().to_string())
+                                            .filter(|x| !x.is_empty())
+                                            .collect();
+                                    }
+                                    ("requires_if", syn::Expr::Tuple(tuple)) => {
+                                        let span = tuple.span();
+                                        let parts: Vec<String> = tuple
+                                            .elems
+                                            .into_iter()
+                                            .filter_map(|elem| match elem {
+                                                syn::Expr::Lit(syn::ExprLit {
+                                                    lit: Lit::Str(v),
+                                                    ..
+                                                }) => Some(v.value()),
+                                                _ => None,
+                                            })
+                                            .collect();
+                                        match <[String; 3]>::try_from(parts) {
+                                            Ok([field, value, required]) => {
+                                                arg_attrs.requires_if.push((field, value, required))
+                                            }
+                                            Err(_) => {
+                                                return Err(syn::Error::new(
+                                                    span,
+                                                    "requires_if expects (\"field\", \"value\", \"required_field\")",
+                                                ))
+                                            }
+                                        }
+                                    }
+                                    (
+                                        "help",
+                                        syn::Expr::Lit(syn::ExprLit {
+                                            lit: Lit::Str(v), ..
+                                        }),
+                                    ) => {
+                                        explicit_help = Some(v.value());
+                                    }
+                                    (
+                                        "alias",
+                                        syn::Expr::Lit(syn::ExprLit {
+                                            lit: Lit::Str(v), ..
+                                        }),
+                                    ) => {
+                                        arg_attrs.aliases.push(v.value());
+                                    }
+                                    (
+                                        "visible_alias",
+                                        syn::Expr::Lit(syn::ExprLit {
+                                            lit: Lit::Str(v), ..
+                                        }),
+                                    ) => {
+                                        arg_attrs.aliases.push(v.value());
+                                        arg_attrs.visible_aliases.push(v.value());
+                                    }
+                                    (
+                                        relation @ ("conflicts_with" | "requires"),
+                                        syn::Expr::Lit(syn::ExprLit {
+                                            lit: Lit::Str(v), ..
+                                        }),
+                                    ) => {
+                                        let names = if relation == "requires" {
+                                            &mut arg_attrs.requires
+                                        } else {
+                                            &mut arg_attrs.conflicts_with
+                                        };
+                                        names.extend(
+                                            v.value()
+                                                .split(',')
+                                                .map(|x| x.trim().to_string())
+                                                .filter(|x| !x.is_empty()),
+                                        );
+                                    }
+                                    (
+                                        "key",
+                                        syn::Expr::Lit(syn::ExprLit {
+                                            lit: Lit::Str(v), ..
+                                        }),
+                                    ) => {
+                                        arg_attrs.merge_key = Some(v.value());
+                                    }
+                                    (
+                                        "deprecated",
+                                        syn::Expr::Lit(syn::ExprLit {
+                                            lit: Lit::Str(v), ..
+                                        }),
+                                    ) => {
+                                        arg_attrs.deprecated = Some(v.value());
+                                    }
+                                    (
+                                        "mutually_required_with",
+                                        syn::Expr::Lit(syn::ExprLit {
+                                            lit: Lit::Str(v), ..
+                                        }),
+                                    ) => {
+                                        arg_attrs.mutually_required_with = Some(v.value());
+                                    }
+                                    (
+                                        "multi_value_behavior",
+                                        syn::Expr::Lit(syn::ExprLit {
+                                            lit: Lit::Str(v), ..
+                                        }),
+                                    ) => match v.value().as_str() {
+                                        "extend" => {
+                                            arg_attrs.multi_value_behavior =
+                                                MultiValueBehavior::Extend
+                                        }
+                                        "extend-unique" => {
+                                            arg_attrs.multi_value_behavior =
+                                                MultiValueBehavior::ExtendUnique
+                                        }
+                                        "overwrite" => {
+                                            arg_attrs.multi_value_behavior =
+                                                MultiValueBehavior::Overwrite
+                                        }
+                                        "merge_by_key" => {
+                                            arg_attrs.multi_value_behavior =
+                                                MultiValueBehavior::MergeByKey
+                                        }
+                                        other => {
+                                            return Err(syn::Error::new(
+                                                attr.span(),
+                                                format!("Invalid multi_value_behavior: {}", other),
+                                            ));
+                                        }
+                                    },
+                                    _ => {}
+                                }
+                            }
+                            Meta::List(list) if list.path.is_ident("flatten") => {
+                                for sub in list.parse_args_with(
+                                    syn::punctuated::Punctuated::<MetaNameValue, syn::Token![,]>::parse_terminated,
+                                )? {
+                                    let (sub_ident, ty) = match (sub.path.get_ident(), &sub.value) {
+                                        (
+                                            Some(sub_ident),
+                                            syn::Expr::Lit(syn::ExprLit {
+                                                lit: Lit::Str(ty), ..
+                                            }),
+                                        ) => (sub_ident.clone(), ty.parse::<syn::Type>()?),
+                                        _ => {
+                                            return Err(syn::Error::new(
+                                                sub.span(),
+                                                "flatten expects field = \"Type\" pairs",
+                                            ))
+                                        }
+                                    };
+                                    arg_attrs.flatten.push((sub_ident, ty));
+                                }
+                            }
+                            Meta::Path(path) => {
+                                if let Some(kw) = path.get_ident() {
+                                    if kw == "positional" {
+                                        arg_attrs.positional = true;
+                                        // Force positional arguments to be CLI-only
+                                        arg_attrs.availability = FieldAvailability::CliOnly;
+                                    } else if kw == "trailing" {
+                                        arg_attrs.trailing = true;
+                                        arg_attrs.positional = true;
+                                        arg_attrs.availability = FieldAvailability::CliOnly;
+                                    } else if kw == "duration" {
+                                        arg_attrs.duration = true;
+                                    } else if kw == "trim" {
+                                        arg_attrs.trim = true;
+                                    } else if kw == "required" {
+                                        arg_attrs.required = true;
+                                    } else if kw == "exclusive_with_config" {
+                                        arg_attrs.exclusive_with_config = true;
+                                    } else if kw == "sensitive" {
+                                        arg_attrs.sensitive = true;
+                                    } else if kw == "hide" {
+                                        arg_attrs.hide = true;
+                                    } else if kw == "count" {
+                                        arg_attrs.count = true;
+                                    } else if kw == "negatable" {
+                                        arg_attrs.negatable = true;
+                                    } else if kw == "rest" {
+                                        arg_attrs.rest = true;
+                                    } else if kw == "require_equals" {
+                                        arg_attrs.require_equals = true;
+                                    } else if kw == "value_enum" {
+                                        arg_attrs.value_enum = true;
+                                    } else {
+                                        return Err(syn::Error::new(
+                                            path.span(),
+                                            format!("Unknown config_arg flag: {}", kw),
+                                        ));
+                                    }
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        }
+
+        // If no #[config_arg], mark as internal.
+        // Otherwise, if user hasn't specified `accept_from`, default to "cli_and_config".
+        // A newtype's only field is its whole point, so it needs no #[config_arg].
+        if !has_config_arg && newtype_name.is_none() {
+            arg_attrs.availability = FieldAvailability::Internal;
+        } else if arg_attrs.availability == FieldAvailability::Internal {
+            arg_attrs.availability = FieldAvailability::CliAndConfig;
+        }
+
+        if explicit_help.is_some() && arg_attrs.help_from.is_some() {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`help` and `help_from` are mutually exclusive",
+            ));
+        }
+        // An explicit `help` replaces the doc comment entirely
+        (arg_attrs.help_text, arg_attrs.long_help) = match explicit_help {
+            Some(help) => (help, None),
+            None => gather_doc_comments(&f.attrs),
+        };
+
+        // Durations are parsed for these shapes only; say so instead of failing in generated code
+        let element = duration_element(&f.ty);
+        let nested = generic_arg(element, "Option").or_else(|| generic_arg(element, "Vec"));
+        let unsupported = match arg_attrs.duration {
+            true => nested.is_some() || is_map(element),
+            false => nested.is_some_and(is_duration),
+        };
+        if unsupported {
+            return Err(syn::Error::new(
+                ident.span(),
+                "Duration fields must be `Duration`, `Option<Duration>` or `Vec<Duration>`",
+            ));
+        }
+
+        // The nested struct itself is read from config; only its listed fields get flags
+        if !arg_attrs.flatten.is_empty() {
+            if !arg_attrs.env.is_empty() {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "`env` cannot be combined with `flatten`",
+                ));
+            }
+            if arg_attrs.availability != FieldAvailability::CliAndConfig {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "`flatten` needs a field accepted from both CLI and config",
+                ));
+            }
+            arg_attrs.availability = FieldAvailability::ConfigOnly;
+        }
+
+        // Whatever keys are left over; there's nothing to name, default or flag
+        if arg_attrs.rest {
+            let string_keyed = matches!(&f.ty, syn::Type::Path(tp) if tp.path.segments.last().is_some_and(|seg| {
+                (seg.ident == "HashMap" || seg.ident == "BTreeMap")
+                    && matches!(&seg.arguments, syn::PathArguments::AngleBracketed(args)
+                        if matches!(args.args.first(), Some(syn::GenericArgument::Type(syn::Type::Path(k))) if k.path.is_ident("String")))
+            }));
+            if !string_keyed {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "`rest` needs a `HashMap<String, serde_json::Value>` (or `BTreeMap`) field",
+                ));
+            }
+            if arg_attrs.availability != FieldAvailability::CliAndConfig
+                || !arg_attrs.env.is_empty()
+                || !arg_attrs.flatten.is_empty()
+                || arg_attrs.required
+                || arg_attrs.default_value.is_some()
+                || arg_attrs.default_value_t.is_some()
+            {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "`rest` takes no `accept_from`, `env`, `flatten`, `required` or default",
+                ));
+            }
+            arg_attrs.availability = FieldAvailability::ConfigOnly;
+        }
+
+        if arg_attrs.exclusive_with_config
+            && matches!(
+                arg_attrs.availability,
+                FieldAvailability::ConfigOnly | FieldAvailability::Internal
+            )
+        {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`exclusive_with_config` needs a field accepted on the command line",
+            ));
+        }
+
+        if arg_attrs.required
+            && (arg_attrs.default_value.is_some() || arg_attrs.default_value_t.is_some())
+        {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`required` fields cannot have a `default_value`",
+            ));
+        }
+        if arg_attrs.default_value.is_some() && arg_attrs.default_value_t.is_some() {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`default_value` and `default_value_t` are mutually exclusive",
+            ));
+        }
+        if let Some(dv) = &arg_attrs.default_value {
+            if arg_attrs.value_parser.is_none() && !arg_attrs.value_enum && !arg_attrs.duration {
+                check_default_value(&f.ty, dv).map_err(|e| syn::Error::new(ident.span(), e))?;
+            }
+        }
+
+        let merge_by_key = matches!(
+            arg_attrs.multi_value_behavior,
+            MultiValueBehavior::MergeByKey
+        );
+        if merge_by_key != arg_attrs.merge_key.is_some() {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`multi_value_behavior = \"merge_by_key\"` and `key = \"...\"` go together",
+            ));
+        }
+        if merge_by_key
+            && (arg_attrs.availability != FieldAvailability::ConfigOnly
+                || generic_arg(&f.ty, "Vec").is_none())
+        {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`merge_by_key` needs a `Vec` field with `accept_from = \"config_only\"`",
+            ));
+        }
+
+        if arg_attrs.value_enum && arg_attrs.value_parser.is_some() {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`value_enum` and `value_parser` are mutually exclusive",
+            ));
+        }
+
+        if arg_attrs.require_equals && arg_attrs.positional {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`require_equals` is only valid on flags, not positionals",
+            ));
+        }
+
+        if arg_attrs.trailing && (generic_arg(&f.ty, "Vec").is_none() || arg_attrs.index.is_some())
+        {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`trailing` needs a `Vec` field and takes no `index`",
+            ));
+        }
+
+        // `--define KEY=VALUE` or `--define KEY VALUE`; nothing else pairs up
+        if is_map(&f.ty) && !matches!(arg_attrs.num_args, None | Some(1) | Some(2)) {
+            return Err(syn::Error::new(
+                ident.span(),
+                "map fields take `num_args = 1` (KEY=VALUE) or `num_args = 2` (KEY VALUE)",
+            ));
+        }
+
+        if arg_attrs.negatable {
+            let is_bool = matches!(&f.ty, syn::Type::Path(tp) if tp.path.is_ident("bool"));
+            if !is_bool
+                || arg_attrs.positional
+                || matches!(
+                    arg_attrs.availability,
+                    FieldAvailability::ConfigOnly | FieldAvailability::Internal
+                )
+            {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "`negatable` needs a `bool` command-line flag",
+                ));
+            }
+        }
+
+        if arg_attrs.count {
+            let unsigned = matches!(&f.ty, syn::Type::Path(tp) if tp.path.get_ident().is_some_and(|i| {
+                ["u8", "u16", "u32", "u64", "u128", "usize"].iter().any(|t| i == t)
+            }));
+            if !unsigned {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "`count` needs an unsigned integer field (u8, u16, u32, u64, u128 or usize)",
+                ));
+            }
+            if arg_attrs.positional
+                || matches!(
+                    arg_attrs.availability,
+                    FieldAvailability::ConfigOnly | FieldAvailability::Internal
+                )
+            {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "`count` needs a command-line flag, not a positional or config-only field",
+                ));
+            }
+        }
+
+        if arg_attrs.index.is_some() && !arg_attrs.positional {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`index` is only valid together with `positional`",
+            ));
+        }
+
+        out.push(FieldInfo {
+            ident,
+            member,
+            ty: f.ty.clone(),
+            arg_attrs,
+        });
+    }
+    if let Some(second) = out.iter().filter(|f| f.arg_attrs.rest).nth(1) {
+        return Err(syn::Error::new(
+            second.ident.span(),
+            "only one field can be `rest`",
+        ));
+    }
+    validate_positional_indices(&out)?;
+    validate_requires_if(&out)?;
+    resolve_flag_relations(&mut out)?;
+    Ok(out)
+}
+
+/// Inherent methods the derive generates, which a `#[config_getters]` getter
+/// (named after its field) would collide with.
+const GENERATED_METHODS: &[&str] = &[
+    "apply_to",
+    "command",
+    "config_json_schema",
+    "diff",
+    "generate_completion",
+    "generate_man_page",
+    "lock_config",
+    "parse",
+    "parse_from",
+    "parse_info",
+    "parse_info_from",
+    "parse_info_from_command",
+    "parse_info_safe",
+    "parse_info_safe_with_fs",
+    "parse_info_with_details",
+    "parse_info_with_fs",
+    "parse_info_with_fs_and_sources",
+    "parse_info_with_raw",
+    "parse_info_with_raw_fs",
+    "parse_info_with_sources",
+    "save_to",
+    "schema",
+    "to_args",
+    "try_parse_from",
+    "try_parse_info",
+    "try_parse_info_from",
+    "try_parse_info_from_command_with_fs",
+    "try_parse_info_with_details_fs",
+    "try_parse_info_with_fs",
+    "try_parse_info_with_fs_and_sources",
+    "verify_against_lock",
+    "write_config_template",
+];
+
+/// `#[config_getters]`: no field may be named like a generated method.
+pub fn validate_getter_names(fields: &[FieldInfo]) -> syn::Result<()> {
+    match fields
+        .iter()
+        .find(|f| GENERATED_METHODS.contains(&f.ident.to_string().as_str()))
+    {
+        Some(f) => Err(syn::Error::new(
+            f.ident.span(),
+            format!(
+                "config_getters: a getter for `{}` would clash with the generated `{}()` method; rename the field",
+                f.ident, f.ident
+            ),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// `#[config_auto_short]`: in declaration order, give each visible flag without a
+/// `short` the first letter of its kebab name no other flag has. Explicit shorts
+/// and `-h` are taken up front. Returns the flags left without one.
+pub fn assign_auto_shorts(fields: &mut [FieldInfo]) -> Vec<String> {
+    let mut taken: Vec<char> = std::iter::once('h')
+        .chain(fields.iter().filter_map(|f| f.arg_attrs.short))
+        .collect();
+    let mut left_out = Vec::new();
+    for f in fields.iter_mut() {
+        let attrs = &f.arg_attrs;
+        let is_flag = matches!(
+            attrs.availability,
+            FieldAvailability::CliOnly | FieldAvailability::CliAndConfig
+        ) && !attrs.positional;
+        if !is_flag || attrs.hide || attrs.short.is_some() {
+            continue;
+        }
+        let name = f.cli_name();
+        match name
+            .chars()
+            .filter(char::is_ascii_alphabetic)
+            .find(|c| !taken.contains(c))
+        {
+            Some(c) => {
+                taken.push(c);
+                f.arg_attrs.short = Some(c);
+            }
+            None => left_out.push(name),
+        }
+    }
+    left_out
+}
+
+/// Each `conflicts_with` / `requires` name must be another field's CLI flag; replaces
+/// the names with those fields' idents, which clap uses as arg ids.
+fn resolve_flag_relations(fields: &mut [FieldInfo]) -> syn::Result<()> {
+    let flags: Vec<(String, String)> = fields
+        .iter()
+        .filter(|f| {
+            matches!(
+                f.arg_attrs.availability,
+                FieldAvailability::CliOnly | FieldAvailability::CliAndConfig
+            )
+        })
+        .map(|f| (f.cli_name(), f.ident.to_string()))
+        .collect();
+    for f in fields.iter_mut() {
+        let own = f.ident.to_string();
+        let relations = [
+            ("conflicts_with", &mut f.arg_attrs.conflicts_with),
+            ("requires", &mut f.arg_attrs.requires),
+        ];
+        for (relation, names) in relations {
+            for name in names.iter_mut() {
+                match flags.iter().find(|(flag, _)| flag == name) {
+                    Some((_, ident)) if *ident != own => *name = ident.clone(),
+                    _ => {
+                        return Err(syn::Error::new(
+                            f.ident.span(),
+                            format!("{}: no other CLI flag named `{}`", relation, name),
+                        ))
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Both fields named by each `requires_if` must exist and take a value from somewhere.
+fn validate_requires_if(fields: &[FieldInfo]) -> syn::Result<()> {
+    for f in fields {
+        for (trigger, _, required) in &f.arg_attrs.requires_if {
+            for name in [trigger, required] {
+                let known = fields.iter().any(|other| {
+                    other.ident == name.as_str()
+                        && other.arg_attrs.availability != FieldAvailability::Internal
+                });
+                if !known {
+                    return Err(syn::Error::new(
+                        f.ident.span(),
+                        format!("requires_if: no #[config_arg] field named `{}`", name),
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// If any positional sets `index`, all must, and together they must be exactly 1..=n.
+/// A `trailing` field comes after them: it must be the last positional declared.
+fn validate_positional_indices(fields: &[FieldInfo]) -> syn::Result<()> {
+    let all: Vec<&FieldInfo> = fields.iter().filter(|f| f.arg_attrs.positional).collect();
+    if let Some(f) = all.iter().rev().skip(1).find(|f| f.arg_attrs.trailing) {
+        return Err(syn::Error::new(
+            f.ident.span(),
+            "a `trailing` field must be the last positional field",
+        ));
+    }
+    let positionals: Vec<&FieldInfo> = all.into_iter().filter(|f| !f.arg_attrs.trailing).collect();
+    if positionals.iter().all(|f| f.arg_attrs.index.is_none()) {
+        return Ok(());
+    }
+    let mut seen = vec![false; positionals.len()];
+    for f in &positionals {
+        let idx = match f.arg_attrs.index {
+            Some(idx) => idx,
+            None => {
+                return Err(syn::Error::new(
+                    f.ident.span(),
+                    "when any positional sets `index`, all positionals must",
+                ))
+            }
+        };
+        if idx == 0 || idx > positionals.len() {
+            return Err(syn::Error::new(
+                f.ident.span(),
+                format!(
+                    "positional index {} out of range: indices must be contiguous from 1 to {}",
+                    idx,
+                    positionals.len()
+                ),
+            ));
+        }
+        if std::mem::replace(&mut seen[idx - 1], true) {
+            return Err(syn::Error::new(
+                f.ident.span(),
+                format!("duplicate positional index {}", idx),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Doc comments as (short help, long help). Lines within a paragraph are joined
+/// with spaces; the first paragraph is the short help, and when there are more,
+/// all paragraphs (blank-line separated) make up the long help.
+pub fn gather_doc_comments(attrs: &[Attribute]) -> (String, Option<String>) {
+    let mut paragraphs: Vec<String> = vec![String::new()];
+    for attr in attrs {
+        // Rust doc comments become `#[doc = "..."]`
+        if attr.path().is_ident("doc") {
+            if let Meta::NameValue(mnv) = &attr.meta {
+                if let syn::Expr::Lit(syn::ExprLit {
+                    lit: Lit::Str(s), ..
+                }) = &mnv.value
+                {
+                    let line = s.value();
+                    let line = line.trim();
+                    let current = paragraphs.last_mut().unwrap();
+                    if line.is_empty() {
+                        if !current.is_empty() {
+                            paragraphs.push(String::new());
+                        }
+                    } else {
+                        if !current.is_empty() {
+                            current.push(' ');
+                        }
+                        current.push_str(line);
+                    }
+                }
+            }
+        }
+    }
+    paragraphs.retain(|p| !p.is_empty());
+    let short = paragraphs.first().cloned().unwrap_or_default();
+    let long = (paragraphs.len() > 1).then(|| paragraphs.join("\n\n"));
+    (short, long)
+}
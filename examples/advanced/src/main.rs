@@ -7,10 +7,25 @@ pub struct ExtraSettings {
     pub allow_guest: Option<bool>,
 }
 
+#[derive(Debug, Clone, Copy, Default, clap::ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+}
+
+// Help text can come from a constant (e.g. swapped per language) via help_from
+const EXTEND_LIST_HELP: &str = "Items added to the list from the config file";
+
 /// A single struct for both CLI and config file usage
 #[derive(ClapConfigFile)]
 #[config_file_name = "advanced-config"]
 #[config_file_formats = "yaml,toml,json"]
+// an empty config file is most likely a mistake (e.g. accidental truncation)
+#[config_file_nonempty]
 struct AdvancedConfig {
     // Use all the defaults:
     // - config_file_name = "advanced-config"
@@ -32,27 +47,61 @@ struct AdvancedConfig {
     pub server_port: u16,
 
     // just specifying --debug will set it to true
-    #[config_arg()]
+    /// This doc comment is replaced by the explicit help below
+    #[config_arg(help = "Enable verbose debug output")]
     pub debug: bool,
 
     // some config files expand environment variables
     // let's say you want the secret never to be set from the CLI so
-    // it's not in the history of the CLI
-    #[config_arg(accept_from = "config_only")]
+    // it's not in the history of the CLI (and `--dump-env` masks it).
+    // SPECIAL_SECRET keeps it out of the committed config file too
+    #[config_arg(accept_from = "config_only", sensitive, env = "SPECIAL_SECRET")]
     pub special_secret: String,
 
-    // this is a nested struct and can only be set from the config file
-    #[config_arg(accept_from = "config_only")]
+    // this is a nested struct read from the config file; the listed fields can
+    // also be overridden with --extra-settings.nesting-level / --extra-settings.allow-guest
+    #[config_arg(flatten(nesting_level = "i64", allow_guest = "Option<bool>"))]
     pub extra_settings: ExtraSettings,
 
     // user will extend the list from the config by adding --extend-list=foo1 --extend-list=foo2
-    #[config_arg(multi_value_behavior = "extend")]
+    #[config_arg(multi_value_behavior = "extend", help_from = EXTEND_LIST_HELP)]
     pub extend_list: Vec<String>,
 
     // user will overwrite the list from the config by adding --overwrite-list=foo1 --overwrite-list=foo2
     #[config_arg(multi_value_behavior = "overwrite")]
     pub overwrite_list: Vec<String>,
 
+    // TLS settings are all-or-nothing: provide all three (from CLI and/or
+    // config file) or none of them.
+    #[config_arg(mutually_required_with = "tls")]
+    pub tls_cert: Option<String>,
+    #[config_arg(mutually_required_with = "tls")]
+    pub tls_key: Option<String>,
+    #[config_arg(mutually_required_with = "tls")]
+    pub tls_ca: Option<String>,
+
+    // In "remote" mode a URL is needed; either value may come from CLI or config
+    #[config_arg(requires_if = ("mode", "remote", "remote_url"))]
+    pub mode: Option<String>,
+    #[config_arg()]
+    pub remote_url: Option<String>,
+
+    // An enum: clap parses it via ValueEnum, the config file via serde
+    /// Log verbosity
+    #[config_arg(name = "log-level", value_enum, default_value = "info")]
+    pub log_level: LogLevel,
+
+    // --log-format on the command line is only accepted together with --log-file
+    #[config_arg(requires = "log-file")]
+    pub log_format: Option<String>,
+    #[config_arg()]
+    pub log_file: Option<String>,
+
+    // Superseded by --database-url; still accepted for now (also as `db_url:` in
+    // config files), with a warning
+    #[config_arg(deprecated = "use --database-url instead", alias = "db_url")]
+    pub db: Option<String>,
+
     // Positional arguments from the CLI, e.g. "file1.txt file2.txt"
     // Positional arguments are always accepted from the CLI only
     // We are assuming those are coming as last arguments. (other cases is not supported)
@@ -76,6 +125,15 @@ impl Default for AdvancedConfig {
             extra_settings: cfg.extra_settings,
             extend_list: cfg.extend_list,
             overwrite_list: cfg.overwrite_list,
+            tls_cert: cfg.tls_cert,
+            tls_key: cfg.tls_key,
+            tls_ca: cfg.tls_ca,
+            mode: cfg.mode,
+            remote_url: cfg.remote_url,
+            log_level: cfg.log_level,
+            log_format: cfg.log_format,
+            log_file: cfg.log_file,
+            db: cfg.db,
             paths: cfg.paths,
             internal_config: "Computed in default initializer".to_string(),
         }
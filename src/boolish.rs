@@ -0,0 +1,10 @@
+//! The bool spellings env vars may use, matching what config files accept.
+
+/// `true`/`yes`/`on`/`1` or `false`/`no`/`off`/`0`, case-insensitive.
+pub fn parse_bool(s: &str) -> Result<bool, String> {
+    match s.trim().to_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Ok(true),
+        "false" | "no" | "off" | "0" => Ok(false),
+        _ => Err("expected true/false, yes/no, on/off or 1/0".to_string()),
+    }
+}
@@ -0,0 +1,27 @@
+//! What a parse found besides the config itself.
+
+use std::path::PathBuf;
+
+/// Returned next to the config by the generated `parse_info_with_details` and
+/// `try_parse_info_with_details_fs`; each parse gets its own.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseDetails {
+    /// Fields (Rust field names) the parse took from the command line.
+    pub cli_set: Vec<&'static str>,
+    /// Every config file the parse merged, lowest priority first (the last one is
+    /// what `parse_info` reports as the used path). A fetched URL is listed as given.
+    pub merged_paths: Vec<PathBuf>,
+}
+
+impl ParseDetails {
+    /// Whether the parse took `field` (the Rust field name) from the CLI rather
+    /// than env, config or defaults.
+    pub fn was_cli_set(&self, field: &str) -> bool {
+        self.cli_set.contains(&field)
+    }
+
+    /// The config files the parse merged: `merged_paths`.
+    pub fn merged_config_paths(&self) -> &[PathBuf] {
+        &self.merged_paths
+    }
+}
@@ -0,0 +1,47 @@
+//! The generated `diff(&a, &b)`: which fields of two configs differ, for change
+//! review and audit logs.
+
+use crate::dump_env::MASK;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+
+/// One field whose value differs between two configs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDiff {
+    /// Rust field name.
+    pub field: &'static str,
+    pub old: Value,
+    pub new: Value,
+    /// `#[config_arg(sensitive)]`: `Display` masks both values.
+    pub sensitive: bool,
+}
+
+impl fmt::Display for FieldDiff {
+    /// `port: 8080 -> 9000`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.sensitive {
+            write!(f, "{}: {} -> {}", self.field, MASK, MASK)
+        } else {
+            write!(f, "{}: {} -> {}", self.field, self.old, self.new)
+        }
+    }
+}
+
+/// A `FieldDiff` if `old` and `new` serialize differently. Compared as serialized,
+/// so field types needn't implement `PartialEq`.
+pub fn compare<T: Serialize + ?Sized>(
+    field: &'static str,
+    old: &T,
+    new: &T,
+    sensitive: bool,
+) -> Option<FieldDiff> {
+    let old = serde_json::to_value(old).unwrap_or(Value::Null);
+    let new = serde_json::to_value(new).unwrap_or(Value::Null);
+    (old != new).then_some(FieldDiff {
+        field,
+        old,
+        new,
+        sensitive,
+    })
+}
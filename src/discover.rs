@@ -0,0 +1,166 @@
+//! Config file discovery: the walk-up rules the generated `parse_info` uses,
+//! callable on their own (e.g. to locate files that sit next to the config).
+
+use crate::{ClapConfigError, ConfigFs, RealFs};
+use std::path::{Path, PathBuf};
+
+/// Walk up from `fs.current_dir()` looking for `{base_name}.{fmt}` for each of
+/// `base_names` and `fmts`. The walk ends at the first directory holding any of
+/// the `stop_at` markers (e.g. `.git`), after searching it; with none it goes
+/// up to the root.
+///
+/// Returns the files found, nearest last (so it wins per key when merged).
+/// Within a directory the earliest-listed base name that has a file wins; more
+/// than one format of that name is an error unless `format_precedence` keeps the
+/// first declared format. Matches in more than one directory are an error unless
+/// `layered`. An unreadable current directory finds nothing.
+pub fn find_config(
+    fs: &dyn ConfigFs,
+    base_names: &[&str],
+    fmts: &[&str],
+    stop_at: &[&str],
+    format_precedence: bool,
+    layered: bool,
+) -> Result<Vec<PathBuf>, ClapConfigError> {
+    let mut dir = match fs.current_dir() {
+        Ok(dir) => dir,
+        Err(_) => return Ok(Vec::new()),
+    };
+    // Nearest first while walking up
+    let mut found: Vec<PathBuf> = Vec::new();
+
+    loop {
+        // At most one per directory, or find_in_dir has already errored
+        if let Some(path) = find_in_dir(fs, &dir, base_names, fmts, format_precedence)?.pop() {
+            found.push(path);
+            if !layered && found.len() > 1 {
+                return Err(ClapConfigError::MultipleConfigFiles(found));
+            }
+        }
+        if has_marker(fs, &dir, stop_at) || !dir.pop() {
+            break;
+        }
+    }
+    // Nearest last, so it wins per key when merged
+    found.reverse();
+    Ok(found)
+}
+
+/// `#[config_file_search]`: try each location group in `search` order and use the
+/// first that has a config file. `"cwd"` walks up like [`find_config`]; `"xdg"` checks
+/// `$XDG_CONFIG_HOME/{base_name}/` (default `~/.config/{base_name}/`); `"home"` checks
+/// `~/.{base_name}.{fmt}`. Several `base_names` are tried in order within each
+/// group. Returns the files found, nearest last.
+pub fn search_config(
+    fs: &dyn ConfigFs,
+    base_names: &[&str],
+    fmts: &[&str],
+    stop_at: &[&str],
+    search: &[&str],
+    format_precedence: bool,
+    layered: bool,
+) -> Result<Vec<PathBuf>, ClapConfigError> {
+    for &group in search {
+        let found = match group {
+            "cwd" => find_config(fs, base_names, fmts, stop_at, format_precedence, layered)?,
+            "xdg" => {
+                let dir = fs
+                    .xdg_config_home()
+                    .or_else(|| fs.home_dir().map(|home| home.join(".config")));
+                let mut found = Vec::new();
+                if let Some(dir) = dir {
+                    for &name in base_names {
+                        found = find_in_dir(fs, &dir.join(name), &[name], fmts, format_precedence)?;
+                        if !found.is_empty() {
+                            break;
+                        }
+                    }
+                }
+                found
+            }
+            "home" => match fs.home_dir() {
+                Some(home) => {
+                    // `.myapprc` stays `~/.myapprc.yaml`, not `~/..myapprc.yaml`
+                    let dotted: Vec<String> = base_names
+                        .iter()
+                        .map(|name| format!(".{}", name.trim_start_matches('.')))
+                        .collect();
+                    let dotted: Vec<&str> = dotted.iter().map(String::as_str).collect();
+                    find_in_dir(fs, &home, &dotted, fmts, format_precedence)?
+                }
+                None => Vec::new(),
+            },
+            _ => Vec::new(),
+        };
+        if !found.is_empty() {
+            return Ok(found);
+        }
+    }
+    Ok(Vec::new())
+}
+
+/// `{dir}/{file_stem}.{fmt}` for each of `fmts`, for the first of `file_stems` that
+/// has any; more than one format of it is an error unless `format_precedence`
+/// keeps the first declared format. A dotfile stem (`.myapprc`) with no such file
+/// also matches the bare `{dir}/{file_stem}`.
+fn find_in_dir(
+    fs: &dyn ConfigFs,
+    dir: &Path,
+    file_stems: &[&str],
+    fmts: &[&str],
+    format_precedence: bool,
+) -> Result<Vec<PathBuf>, ClapConfigError> {
+    for file_stem in file_stems {
+        let mut found: Vec<PathBuf> = fmts
+            .iter()
+            .map(|f| dir.join(format!("{}.{}", file_stem, f)))
+            .filter(|candidate| fs.is_file(candidate))
+            .collect();
+        if found.len() > 1 && format_precedence {
+            found.truncate(1);
+        }
+        if found.len() > 1 {
+            return Err(ClapConfigError::MultipleConfigFiles(found));
+        }
+        // Dotfiles conventionally go without an extension; their format is sniffed
+        if found.is_empty() && file_stem.starts_with('.') {
+            found.extend(Some(dir.join(file_stem)).filter(|bare| fs.is_file(bare)));
+        }
+        if !found.is_empty() {
+            return Ok(found);
+        }
+    }
+    Ok(Vec::new())
+}
+
+/// Whether `dir` has an entry (file or directory) named like one of `markers`.
+fn has_marker(fs: &dyn ConfigFs, dir: &Path, markers: &[&str]) -> bool {
+    if markers.is_empty() {
+        return false;
+    }
+    let entries = fs.read_dir(dir).unwrap_or_default();
+    markers.iter().any(|m| entries.contains(&dir.join(m)))
+}
+
+/// Nearest `file_name` at or above `fs.current_dir()` (e.g. `Cargo.toml`).
+pub fn find_upwards(fs: &dyn ConfigFs, file_name: &str) -> Option<PathBuf> {
+    let mut dir = fs.current_dir().ok()?;
+    loop {
+        let candidate = dir.join(file_name);
+        if fs.is_file(&candidate) {
+            return Some(candidate);
+        }
+        if !dir.pop() {
+            return None;
+        }
+    }
+}
+
+/// The config file `parse_info` would discover for `config_file_name = base_name`
+/// and `config_file_formats = formats`, starting from the real current directory.
+pub fn config_path_for(
+    base_name: &str,
+    formats: &[&str],
+) -> Result<Option<PathBuf>, ClapConfigError> {
+    Ok(find_config(&RealFs, &[base_name], formats, &[], false, false)?.pop())
+}
@@ -0,0 +1,59 @@
+//! `--dump-env`: the effective config as `KEY=value` lines, named the way env
+//! loading reads them, so the output can be fed back in as an env file.
+
+use serde_json::Value;
+use std::fmt::Write;
+
+/// Printed instead of a `sensitive` field's value unless `--unsafe` is given.
+pub const MASK: &str = "********";
+
+/// One field of the effective config.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvEntry {
+    /// The field's first `env` name, or its config key in SCREAMING_SNAKE_CASE.
+    pub name: &'static str,
+    pub value: Value,
+    /// `#[config_arg(sensitive)]`: masked unless `show_sensitive`.
+    pub sensitive: bool,
+}
+
+/// One `NAME=value` line per entry; `null`s are skipped. Lists are comma-joined,
+/// as env loading splits them; values with whitespace or quotes are double-quoted.
+pub fn render(entries: &[EnvEntry], show_sensitive: bool) -> String {
+    let mut out = String::new();
+    for e in entries {
+        if e.value.is_null() {
+            continue;
+        }
+        let value = if e.sensitive && !show_sensitive {
+            MASK.to_string()
+        } else {
+            quoted(&env_value(&e.value))
+        };
+        let _ = writeln!(out, "{}={}", e.name, value);
+    }
+    out
+}
+
+fn env_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items
+            .iter()
+            .filter(|v| !v.is_null())
+            .map(env_value)
+            .collect::<Vec<_>>()
+            .join(","),
+        other => other.to_string(),
+    }
+}
+
+fn quoted(s: &str) -> String {
+    if s.chars()
+        .any(|c| c.is_whitespace() || matches!(c, '"' | '\'' | '#' | '$' | '\\'))
+    {
+        Value::from(s).to_string()
+    } else {
+        s.to_string()
+    }
+}
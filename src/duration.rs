@@ -0,0 +1,199 @@
+//! `Duration` parsing shared by the CLI (`value_parser`) and config (`deserialize_with`) sides.
+//!
+//! Accepts one or more `<number><unit>` parts, e.g. `"90s"`, `"5m"`, `"1h30m"`, `"1h 30m"`.
+//! Units: `ms`, `s`, `m`, `h`, `d`. A bare number is seconds.
+
+use serde::de::{self, DeserializeSeed, Deserializer, SeqAccess, Visitor};
+use std::fmt;
+use std::time::Duration;
+
+pub fn parse_duration(input: &str) -> Result<Duration, String> {
+    let s = input.trim();
+    if s.is_empty() {
+        return Err("empty duration".to_string());
+    }
+    if let Ok(secs) = s.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+
+    let mut total = Duration::ZERO;
+    let mut rest = s;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if digits == 0 {
+            return Err(format!("invalid duration {:?}: expected a number", input));
+        }
+        let n: u64 = rest[..digits]
+            .parse()
+            .map_err(|e| format!("invalid duration {:?}: {}", input, e))?;
+        rest = &rest[digits..];
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit() || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let secs = |per: u64| n.checked_mul(per).map(Duration::from_secs);
+        let part = match &rest[..unit_len] {
+            "ms" => Some(Duration::from_millis(n)),
+            "s" => Some(Duration::from_secs(n)),
+            "m" => secs(60),
+            "h" => secs(60 * 60),
+            "d" => secs(60 * 60 * 24),
+            "" => return Err(format!("invalid duration {:?}: missing unit", input)),
+            other => {
+                return Err(format!(
+                    "invalid duration {:?}: unknown unit {:?}",
+                    input, other
+                ))
+            }
+        };
+        total = part
+            .and_then(|part| total.checked_add(part))
+            .ok_or_else(|| format!("invalid duration {:?}: duration out of range", input))?;
+        rest = rest[unit_len..].trim_start();
+    }
+    Ok(total)
+}
+
+/// `#[serde(deserialize_with = "...")]` counterpart of [`parse_duration`].
+/// Also accepts a plain integer number of seconds.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    BUILT_IN.deserialize(deserializer)
+}
+
+/// [`deserialize`] for an `Option<Duration>` field: `null` is `None`.
+pub fn deserialize_option<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_option(OptionVisitor(BUILT_IN))
+}
+
+/// [`deserialize`] for each element of a `Vec<Duration>` field.
+pub fn deserialize_vec<'de, D>(deserializer: D) -> Result<Vec<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_seq(SeqVisitor(BUILT_IN))
+}
+
+const BUILT_IN: DurationSeed = DurationSeed {
+    parse: parse_duration,
+    expecting: "a duration like \"90s\" or \"1h30m\", or a number of seconds",
+};
+
+/// One duration, as a string in `parse`'s grammar or a number of seconds.
+#[derive(Clone, Copy)]
+struct DurationSeed {
+    parse: fn(&str) -> Result<Duration, String>,
+    expecting: &'static str,
+}
+
+impl<'de> DeserializeSeed<'de> for DurationSeed {
+    type Value = Duration;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Duration, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl Visitor<'_> for DurationSeed {
+    type Value = Duration;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.expecting)
+    }
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Duration, E> {
+        (self.parse)(v).map_err(E::custom)
+    }
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Duration, E> {
+        Ok(Duration::from_secs(v))
+    }
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Duration, E> {
+        u64::try_from(v)
+            .map(Duration::from_secs)
+            .map_err(|_| E::custom("duration cannot be negative"))
+    }
+}
+
+struct OptionVisitor(DurationSeed);
+
+impl<'de> Visitor<'de> for OptionVisitor {
+    type Value = Option<Duration>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} or null", self.0.expecting)
+    }
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        self.0.deserialize(deserializer).map(Some)
+    }
+}
+
+struct SeqVisitor(DurationSeed);
+
+impl<'de> Visitor<'de> for SeqVisitor {
+    type Value = Vec<Duration>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a list of durations ({})", self.0.expecting)
+    }
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut out = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(d) = seq.next_element_seed(self.0)? {
+            out.push(d);
+        }
+        Ok(out)
+    }
+}
+
+/// `#[config_arg(parse_with = "humantime")]`: the same two entry points, with
+/// humantime's grammar (`"2h 30min"`, `"1day"`, `"15 seconds"`, ...).
+#[cfg(feature = "humantime")]
+pub mod humantime {
+    use super::{DurationSeed, OptionVisitor, SeqVisitor};
+    use serde::de::{DeserializeSeed, Deserializer};
+    use std::time::Duration;
+
+    pub fn parse_duration(input: &str) -> Result<Duration, String> {
+        ::humantime::parse_duration(input.trim())
+            .map_err(|e| format!("invalid duration {:?}: {}", input, e))
+    }
+
+    /// Also accepts a plain integer number of seconds, like [`super::deserialize`].
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        HUMANTIME.deserialize(deserializer)
+    }
+
+    /// [`deserialize`] for an `Option<Duration>` field: `null` is `None`.
+    pub fn deserialize_option<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_option(OptionVisitor(HUMANTIME))
+    }
+
+    /// [`deserialize`] for each element of a `Vec<Duration>` field.
+    pub fn deserialize_vec<'de, D>(deserializer: D) -> Result<Vec<Duration>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(SeqVisitor(HUMANTIME))
+    }
+
+    const HUMANTIME: DurationSeed = DurationSeed {
+        parse: parse_duration,
+        expecting: "a duration like \"2h 30min\", or a number of seconds",
+    };
+}
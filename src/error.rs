@@ -0,0 +1,102 @@
+use std::fmt;
+use std::path::PathBuf;
+
+/// Why a generated `try_parse_info*` call failed.
+///
+/// The non-`try` methods print this and exit with status 2 instead
+/// (or, for [`ClapConfigError::Cli`], let clap print help/usage and exit;
+/// for [`ClapConfigError::Display`], print the output and exit with 0).
+#[derive(Debug)]
+pub enum ClapConfigError {
+    /// Bad command line. Also covers `--help` and `--version`, which clap
+    /// reports as errors; `err.exit()` prints them the usual way.
+    Cli(clap::Error),
+    /// Discovery found more than one config file (same directory, or walking up).
+    MultipleConfigFiles(Vec<PathBuf>),
+    /// A config file given with `--config-file`, or found by discovery, couldn't be read.
+    ConfigReadError {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// The config sources couldn't be parsed or don't match the struct.
+    ConfigDeserializeError(config::ConfigError),
+    /// A config file's format isn't one of the declared formats.
+    FormatGuessFailed(PathBuf),
+    /// Values were found but are invalid: unparsable env/CLI values, missing
+    /// `mutually_required_with`/`requires_if` partners, an empty config file, ...
+    Invalid(String),
+    /// Loading panicked (caught by `parse_info_safe`); holds the panic message.
+    Panicked(String),
+    /// Not a failure: `--dump-schema`, `--generate-config` or `--dump-env` asked
+    /// for this output instead of a config. Like clap's `DisplayHelp`, `exit()`
+    /// prints it to stdout and exits with 0.
+    Display(String),
+}
+
+impl ClapConfigError {
+    /// `Panicked` from a `catch_unwind` payload, keeping its message if it has one.
+    pub fn from_panic(payload: Box<dyn std::any::Any + Send>) -> Self {
+        let msg = match payload.downcast::<String>() {
+            Ok(msg) => *msg,
+            Err(payload) => match payload.downcast::<&'static str>() {
+                Ok(msg) => msg.to_string(),
+                Err(_) => "unknown panic".to_string(),
+            },
+        };
+        ClapConfigError::Panicked(msg)
+    }
+
+    /// Report the error like the non-`try` methods do, and exit.
+    pub fn exit(&self) -> ! {
+        match self {
+            ClapConfigError::Cli(e) => e.exit(),
+            ClapConfigError::Display(out) => {
+                print!("{}", out);
+                std::process::exit(0);
+            }
+            other => {
+                eprintln!("Error: {}", other);
+                std::process::exit(2);
+            }
+        }
+    }
+}
+
+impl fmt::Display for ClapConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClapConfigError::Cli(e) => write!(f, "{}", e),
+            ClapConfigError::MultipleConfigFiles(paths) => {
+                write!(f, "multiple config files found: {:?}", paths)
+            }
+            ClapConfigError::ConfigReadError { path, source } => {
+                write!(
+                    f,
+                    "failed to read config file {}: {}",
+                    path.display(),
+                    source
+                )
+            }
+            ClapConfigError::ConfigDeserializeError(e) => write!(f, "invalid config: {}", e),
+            ClapConfigError::FormatGuessFailed(path) => write!(
+                f,
+                "cannot tell the format of {}; use a known extension or PATH:FORMAT",
+                path.display()
+            ),
+            ClapConfigError::Invalid(msg) => f.write_str(msg),
+            ClapConfigError::Panicked(msg) => write!(f, "loading the config panicked: {}", msg),
+            ClapConfigError::Display(out) => f.write_str(out),
+        }
+    }
+}
+
+impl std::error::Error for ClapConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ClapConfigError::Cli(e) => Some(e),
+            ClapConfigError::ConfigReadError { source, .. } => Some(source),
+            ClapConfigError::ConfigDeserializeError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
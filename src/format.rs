@@ -0,0 +1,54 @@
+//! Config format names and how they map to `config` parsers. Shared by the
+//! generated `parse_info` for discovered files, `--config-file` and `--config`.
+
+use config::{FileFormat, Format};
+use std::path::{Path, PathBuf};
+
+/// The declared format matching `path`'s extension (case-insensitive).
+pub fn guess_format(path: &Path, known_formats: &[&'static str]) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    known_formats.iter().copied().find(|f| *f == ext)
+}
+
+/// `base.conf:toml` => (`base.conf`, toml); otherwise guess from the extension.
+pub fn split_format(
+    path: &Path,
+    known_formats: &[&'static str],
+) -> (PathBuf, Option<&'static str>) {
+    if let Some((p, f)) = path.to_str().and_then(|s| s.rsplit_once(':')) {
+        if let Some(&fmt) = known_formats.iter().find(|k| k.eq_ignore_ascii_case(f)) {
+            return (PathBuf::from(p), Some(fmt));
+        }
+    }
+    (path.to_path_buf(), guess_format(path, known_formats))
+}
+
+/// The one place format names map to parsers. Declared formats are checked
+/// against the same list at compile time, so `None` means an undeclared name.
+pub fn file_format(fmt: &str) -> Option<FileFormat> {
+    match fmt {
+        "yaml" | "yml" => Some(FileFormat::Yaml),
+        "json" => Some(FileFormat::Json),
+        // JSON5 is a superset of JSONC: comments and trailing commas
+        "jsonc" | "json5" => Some(FileFormat::Json5),
+        "toml" => Some(FileFormat::Toml),
+        "ini" => Some(FileFormat::Ini),
+        "ron" => Some(FileFormat::Ron),
+        _ => None,
+    }
+}
+
+/// First of `known_formats` that parses `raw` into a non-empty table.
+/// YAML accepts almost any text as a scalar, so "parses" alone isn't enough.
+pub fn detect_format(
+    raw: &str,
+    known_formats: &[&'static str],
+) -> Option<(&'static str, FileFormat)> {
+    known_formats.iter().copied().find_map(|f| {
+        let file_format = file_format(f)?;
+        match file_format.parse(None, raw) {
+            Ok(map) if !map.is_empty() => Some((f, file_format)),
+            _ => None,
+        }
+    })
+}
@@ -0,0 +1,270 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The filesystem calls made by the generated `parse_info` code while
+/// discovering and reading config files.
+pub trait ConfigFs {
+    /// Directory discovery starts walking up from.
+    fn current_dir(&self) -> io::Result<PathBuf>;
+    /// Whether `path` is a regular file discovery may read.
+    fn is_file(&self, path: &Path) -> bool;
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+    /// Entries (files and directories) directly inside `dir`.
+    fn read_dir(&self, dir: &Path) -> io::Result<Vec<PathBuf>>;
+    /// All of stdin, for `--config-file -`.
+    fn read_stdin(&self) -> io::Result<String> {
+        let mut contents = String::new();
+        io::Read::read_to_string(&mut io::stdin(), &mut contents)?;
+        Ok(contents)
+    }
+    /// The user's home directory, for `#[config_file_search]`'s `home` and `xdg`.
+    fn home_dir(&self) -> Option<PathBuf> {
+        None
+    }
+    /// `$XDG_CONFIG_HOME`, if set (on Windows, else `%APPDATA%`); `xdg` search
+    /// falls back to `~/.config` otherwise.
+    fn xdg_config_home(&self) -> Option<PathBuf> {
+        None
+    }
+}
+
+/// How many directories deep `#[config_file_recursive]` descends below its root.
+pub const RECURSIVE_MAX_DEPTH: usize = 8;
+
+/// All files under `root`, at most `max_depth` directories deep, in sorted path order.
+pub fn walk_files(fs: &dyn ConfigFs, root: &Path, max_depth: usize) -> Vec<PathBuf> {
+    let mut out = Vec::new();
+    let mut entries = fs.read_dir(root).unwrap_or_default();
+    entries.sort();
+    for entry in entries {
+        if fs.is_file(&entry) {
+            out.push(entry);
+        } else if max_depth > 0 {
+            out.extend(walk_files(fs, &entry, max_depth - 1));
+        }
+    }
+    out
+}
+
+/// Expand a leading `~` (via `fs.home_dir()`) and `$VAR` / `${VAR}` env references
+/// in a `--config-file` path, e.g. `/configs/${ENV}/app.yaml`. An unset variable is
+/// an error rather than an empty string; `$$` is a literal `$`.
+pub fn expand_path(path: &Path, fs: &dyn ConfigFs) -> Result<PathBuf, String> {
+    // Non-UTF-8 paths can't hold anything to expand that we could read
+    let Some(raw) = path.to_str() else {
+        return Ok(path.to_path_buf());
+    };
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+    if rest == "~" || rest.starts_with("~/") || (cfg!(windows) && rest.starts_with("~\\")) {
+        let home = fs
+            .home_dir()
+            .ok_or_else(|| format!("cannot expand ~ in {}: no home directory", raw))?;
+        out.push_str(&home.to_string_lossy());
+        rest = &rest[1..];
+    }
+    while let Some(i) = rest.find('$') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i + 1..];
+        let (name, after) = if let Some(braced) = rest.strip_prefix('{') {
+            let end = braced
+                .find('}')
+                .ok_or_else(|| format!("unclosed ${{ in {}", raw))?;
+            (&braced[..end], &braced[end + 1..])
+        } else if let Some(after) = rest.strip_prefix('$') {
+            out.push('$');
+            rest = after;
+            continue;
+        } else {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            (&rest[..end], &rest[end..])
+        };
+        if name.is_empty() {
+            out.push('$');
+            continue;
+        }
+        let value = std::env::var(name).map_err(|_| format!("{} is not set (in {})", name, raw))?;
+        out.push_str(&value);
+        rest = after;
+    }
+    out.push_str(rest);
+    Ok(PathBuf::from(out))
+}
+
+/// Split a `--config-file a.yaml,b.yaml` argument into its paths, in order.
+/// `\,` is a literal comma; other backslashes (Windows paths) are kept as they are.
+pub fn split_path_list(arg: &Path) -> Vec<PathBuf> {
+    let Some(raw) = arg.to_str() else {
+        return vec![arg.to_path_buf()];
+    };
+    let mut paths = Vec::new();
+    let mut current = String::new();
+    let mut chars = raw.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&',') => {
+                current.push(',');
+                chars.next();
+            }
+            ',' => {
+                if !current.is_empty() {
+                    paths.push(PathBuf::from(std::mem::take(&mut current)));
+                }
+            }
+            other => current.push(other),
+        }
+    }
+    if !current.is_empty() {
+        paths.push(PathBuf::from(current));
+    }
+    paths
+}
+
+/// The real filesystem. Used by `parse_info()`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RealFs;
+
+impl ConfigFs for RealFs {
+    fn current_dir(&self) -> io::Result<PathBuf> {
+        std::env::current_dir()
+    }
+    /// Regular files only: a FIFO, socket or device would make discovery's
+    /// `read_to_string` block or misbehave, so it's treated as absent.
+    fn is_file(&self, path: &Path) -> bool {
+        std::fs::metadata(path)
+            .map(|m| m.file_type().is_file())
+            .unwrap_or(false)
+    }
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        std::fs::read_to_string(path)
+    }
+    fn read_dir(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        std::fs::read_dir(dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect()
+    }
+    fn home_dir(&self) -> Option<PathBuf> {
+        let var = if cfg!(windows) { "USERPROFILE" } else { "HOME" };
+        absolute_env_path(var)
+    }
+    fn xdg_config_home(&self) -> Option<PathBuf> {
+        let xdg = absolute_env_path("XDG_CONFIG_HOME");
+        if cfg!(windows) {
+            xdg.or_else(|| absolute_env_path("APPDATA"))
+        } else {
+            xdg
+        }
+    }
+}
+
+/// An env var holding an absolute path; empty or relative values are ignored, per XDG.
+fn absolute_env_path(var: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(std::env::var_os(var)?);
+    path.is_absolute().then_some(path)
+}
+
+/// A fixed set of in-memory files, for exercising discovery and merging
+/// without touching the real filesystem.
+///
+/// Relative paths (both registered files and lookups) resolve against `cwd`.
+/// An empty `MemoryFs` behaves as if discovery found nothing.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryFs {
+    cwd: PathBuf,
+    files: BTreeMap<PathBuf, String>,
+    home: Option<PathBuf>,
+    xdg_config_home: Option<PathBuf>,
+    stdin: String,
+}
+
+impl MemoryFs {
+    pub fn new(cwd: impl Into<PathBuf>) -> Self {
+        Self {
+            cwd: cwd.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Set the home directory (unset by default).
+    pub fn home(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.home = Some(dir.into());
+        self
+    }
+
+    /// Set what `--config-file -` reads (empty by default).
+    pub fn stdin(mut self, contents: impl Into<String>) -> Self {
+        self.stdin = contents.into();
+        self
+    }
+
+    /// Set `$XDG_CONFIG_HOME` (unset by default).
+    pub fn xdg_config_home(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.xdg_config_home = Some(dir.into());
+        self
+    }
+
+    /// Add a file, e.g. `.file("/project/app.yaml", "port: 1")`.
+    pub fn file(mut self, path: impl AsRef<Path>, contents: impl Into<String>) -> Self {
+        let path = self.resolve(path.as_ref());
+        self.files.insert(path, contents.into());
+        self
+    }
+
+    fn resolve(&self, path: &Path) -> PathBuf {
+        if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.cwd.join(path)
+        }
+    }
+}
+
+impl ConfigFs for MemoryFs {
+    fn current_dir(&self) -> io::Result<PathBuf> {
+        Ok(self.cwd.clone())
+    }
+    fn is_file(&self, path: &Path) -> bool {
+        self.files.contains_key(&self.resolve(path))
+    }
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        self.files.get(&self.resolve(path)).cloned().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} not found", path.display()),
+            )
+        })
+    }
+    fn read_dir(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        // Directories are implied by the files registered below them
+        let dir = self.resolve(dir);
+        let mut entries: Vec<PathBuf> = self
+            .files
+            .keys()
+            .filter_map(|p| {
+                let first = p.strip_prefix(&dir).ok()?.components().next()?;
+                Some(dir.join(first))
+            })
+            .collect();
+        if entries.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} not found", dir.display()),
+            ));
+        }
+        entries.sort();
+        entries.dedup();
+        Ok(entries)
+    }
+    fn read_stdin(&self) -> io::Result<String> {
+        Ok(self.stdin.clone())
+    }
+    fn home_dir(&self) -> Option<PathBuf> {
+        self.home.clone()
+    }
+    fn xdg_config_home(&self) -> Option<PathBuf> {
+        self.xdg_config_home.clone()
+    }
+}
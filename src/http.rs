@@ -0,0 +1,42 @@
+//! `--config-file https://...`: config fetched over HTTP(S). Fetching needs the
+//! `http` feature; without it a URL is recognized but fails with a clear error.
+
+use std::io;
+use std::path::Path;
+
+/// Whether a `--config-file` value names an `http://` or `https://` URL.
+pub fn is_url(path: &Path) -> bool {
+    path.to_str().is_some_and(|s| {
+        let lower = s.to_ascii_lowercase();
+        lower.starts_with("http://") || lower.starts_with("https://")
+    })
+}
+
+/// The declared format matching the extension of the URL's path, ignoring any
+/// query string or fragment (`.../config.yaml?ref=main`).
+pub fn guess_format(url: &Path, known_formats: &[&'static str]) -> Option<&'static str> {
+    let s = url.to_str()?;
+    let path = s.split(['?', '#']).next().unwrap_or(s);
+    let (_, file) = path.rsplit_once('/')?;
+    let (_, ext) = file.rsplit_once('.')?;
+    let ext = ext.to_lowercase();
+    known_formats.iter().copied().find(|f| *f == ext)
+}
+
+/// GET `url` and return the body. A non-2xx status is an error.
+#[cfg(feature = "http")]
+pub fn fetch(url: &Path) -> io::Result<String> {
+    let url = url.to_str().unwrap_or_default();
+    reqwest::blocking::get(url)
+        .and_then(|resp| resp.error_for_status())
+        .and_then(|resp| resp.text())
+        .map_err(io::Error::other)
+}
+
+#[cfg(not(feature = "http"))]
+pub fn fetch(_url: &Path) -> io::Result<String> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "loading config from a URL needs clap-config-file's `http` feature",
+    ))
+}
@@ -0,0 +1,177 @@
+//! `include = ["base.toml", ...]` at the top level of a config file, under
+//! `#[config_file_include]`: the named files are loaded first, below the
+//! including file, so it can override them.
+
+use crate::{format, http, ClapConfigError, ConfigFs};
+use config::{ConfigError, FileFormat, Format, Map, Source, Value};
+use std::path::{Component, Path, PathBuf};
+
+/// The top-level key naming the files to include.
+pub const KEY: &str = "include";
+
+/// Every file `path` includes, transitively and depth-first, lowest priority
+/// first: an included file's own includes come before it, and later entries of
+/// a list win over earlier ones. The including file itself is not in the list.
+///
+/// Relative entries resolve against the including file's directory. A file that
+/// (directly or not) includes itself is an error; one file included twice from
+/// different places is fine.
+pub fn resolve(
+    fs: &dyn ConfigFs,
+    path: &Path,
+    contents: &str,
+    file_format: FileFormat,
+    known_formats: &[&'static str],
+) -> Result<Vec<(PathBuf, String, FileFormat)>, ClapConfigError> {
+    let mut out = Vec::new();
+    let mut stack = vec![normalize(path)];
+    collect(
+        fs,
+        path,
+        contents,
+        file_format,
+        known_formats,
+        &mut stack,
+        &mut out,
+    )?;
+    Ok(out)
+}
+
+fn collect(
+    fs: &dyn ConfigFs,
+    path: &Path,
+    contents: &str,
+    file_format: FileFormat,
+    known_formats: &[&'static str],
+    stack: &mut Vec<PathBuf>,
+    out: &mut Vec<(PathBuf, String, FileFormat)>,
+) -> Result<(), ClapConfigError> {
+    let entries = entries(path, contents, file_format)?;
+    if entries.is_empty() {
+        return Ok(());
+    }
+    if http::is_url(path) {
+        return Err(ClapConfigError::Invalid(format!(
+            "{}: `{}` is not supported in a fetched config",
+            path.display(),
+            KEY
+        )));
+    }
+    // Stdin has no directory of its own
+    let dir = match path.parent() {
+        Some(dir) if path.as_os_str() != "-" => dir.to_path_buf(),
+        _ => fs.current_dir().unwrap_or_default(),
+    };
+
+    for entry in entries {
+        let entry = crate::expand_path(Path::new(&entry), fs).map_err(ClapConfigError::Invalid)?;
+        let included = normalize(&dir.join(entry));
+        if let Some(start) = stack.iter().position(|p| *p == included) {
+            let cycle: Vec<String> = stack[start..]
+                .iter()
+                .chain([&included])
+                .map(|p| p.display().to_string())
+                .collect();
+            return Err(ClapConfigError::Invalid(format!(
+                "cyclic config include: {}",
+                cycle.join(" -> ")
+            )));
+        }
+
+        let contents =
+            fs.read_to_string(&included)
+                .map_err(|source| ClapConfigError::ConfigReadError {
+                    path: included.clone(),
+                    source,
+                })?;
+        if contents.trim().is_empty() {
+            continue;
+        }
+        let file_format = match format::guess_format(&included, known_formats) {
+            Some(fmt) => format::file_format(fmt),
+            None if included.extension().is_none() => {
+                format::detect_format(&contents, known_formats).map(|(_, f)| f)
+            }
+            None => None,
+        }
+        .ok_or_else(|| ClapConfigError::FormatGuessFailed(included.clone()))?;
+
+        stack.push(included.clone());
+        collect(
+            fs,
+            &included,
+            &contents,
+            file_format,
+            known_formats,
+            stack,
+            out,
+        )?;
+        stack.pop();
+        out.push((included, contents, file_format));
+    }
+    Ok(())
+}
+
+/// The `include` entries of a file: a single path or a list of them. A file that
+/// doesn't parse has none here; loading it reports the error.
+fn entries(
+    path: &Path,
+    contents: &str,
+    file_format: FileFormat,
+) -> Result<Vec<String>, ClapConfigError> {
+    let Ok(mut table) = file_format.parse(None, contents) else {
+        return Ok(Vec::new());
+    };
+    let Some(value) = table.remove(KEY) else {
+        return Ok(Vec::new());
+    };
+    let invalid = |_| {
+        ClapConfigError::Invalid(format!(
+            "{}: `{}` must be a path or a list of paths",
+            path.display(),
+            KEY
+        ))
+    };
+    match value.clone().into_array() {
+        Ok(items) => items
+            .into_iter()
+            .map(|item| item.into_string().map_err(invalid))
+            .collect(),
+        Err(_) => value.into_string().map(|s| vec![s]).map_err(invalid),
+    }
+}
+
+/// `a/./b/../c` => `a/c`, without touching the filesystem, so the same file
+/// reached by two spellings is read, reported and caught as a cycle as one.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(out.components().next_back(), Some(Component::Normal(_))) =>
+            {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// A config file source with its top-level `include` key dropped, so it isn't
+/// mistaken for a config field.
+#[derive(Debug, Clone)]
+pub struct WithoutInclude<S>(pub S);
+
+impl<S: Source + Clone + Send + Sync + 'static> Source for WithoutInclude<S> {
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>, ConfigError> {
+        let mut table = self.0.collect()?;
+        table.remove(KEY);
+        Ok(table)
+    }
+}
@@ -0,0 +1,84 @@
+//! Config lock files: the generated `lock_config` records the resolved config and
+//! the files it came from; `verify_against_lock` fails when the config has drifted.
+
+use crate::ClapConfigError;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+/// Replace each of `fields` in `config` with a digest of its value, so a lock file
+/// holds no `sensitive` value in plaintext but still changes when one does. Both
+/// `lock_config` and `verify_against_lock` redact, so the digests are compared.
+pub fn redact(config: &mut Value, fields: &[&str]) {
+    let Value::Object(map) = config else {
+        return;
+    };
+    for field in fields {
+        if let Some(value) = map.get_mut(*field) {
+            *value = Value::String(format!(
+                "fnv1a64:{:016x}",
+                fnv1a64(value.to_string().as_bytes())
+            ));
+        }
+    }
+}
+
+/// FNV-1a, 64-bit: stable across builds and platforms, which is all a lock file needs.
+fn fnv1a64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Write `{"config": ..., "sources": [...]}` as pretty JSON to `path`.
+pub fn write(path: &Path, config: Value, sources: &[PathBuf]) -> Result<(), ClapConfigError> {
+    let lock = json!({ "config": config, "sources": sources });
+    let text = serde_json::to_string_pretty(&lock).unwrap_or_default() + "\n";
+    std::fs::write(path, text).map_err(|e| {
+        ClapConfigError::Invalid(format!(
+            "failed to write lock file {}: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+/// Compare `config` with the one recorded in the lock file at `path`. Only the
+/// config counts: the same values from different files still match.
+pub fn verify(path: &Path, config: &Value) -> Result<(), ClapConfigError> {
+    let text =
+        std::fs::read_to_string(path).map_err(|source| ClapConfigError::ConfigReadError {
+            path: path.to_path_buf(),
+            source,
+        })?;
+    let lock: Value = serde_json::from_str(&text).map_err(|e| {
+        ClapConfigError::Invalid(format!("invalid lock file {}: {}", path.display(), e))
+    })?;
+    let locked = lock.get("config").unwrap_or(&Value::Null);
+    if locked == config {
+        return Ok(());
+    }
+
+    let drifted: Vec<&str> = match (locked, config) {
+        (Value::Object(locked), Value::Object(current)) => {
+            let mut keys: Vec<&str> = locked
+                .keys()
+                .chain(current.keys())
+                .map(String::as_str)
+                .filter(|k| locked.get(*k) != current.get(*k))
+                .collect();
+            keys.sort_unstable();
+            keys.dedup();
+            keys
+        }
+        _ => Vec::new(),
+    };
+    Err(ClapConfigError::Invalid(format!(
+        "config drifted from lock file {}: {}",
+        path.display(),
+        if drifted.is_empty() {
+            "config differs".to_string()
+        } else {
+            format!("changed {}", drifted.join(", "))
+        }
+    )))
+}
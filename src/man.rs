@@ -0,0 +1,18 @@
+//! The generated `generate_man_page`: a roff man page for the CLI. Rendering needs
+//! the `man` feature; without it the call fails with a clear error.
+
+use std::io;
+
+/// Render `cmd` as a man page to `w`.
+#[cfg(feature = "man")]
+pub fn render(cmd: clap::Command, w: &mut dyn io::Write) -> io::Result<()> {
+    clap_mangen::Man::new(cmd).render(w)
+}
+
+#[cfg(not(feature = "man"))]
+pub fn render(_cmd: clap::Command, _w: &mut dyn io::Write) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "man page generation needs clap-config-file's `man` feature",
+    ))
+}
@@ -0,0 +1,61 @@
+//! `multi_value_behavior = "merge_by_key"`: a list of tables layered across config
+//! sources by an identifying key, instead of the later source replacing it whole.
+
+use config::{ConfigError, Map, Source};
+use serde_json::Value;
+
+/// Upsert the `field` list of each of `layers` (lowest priority first) by each
+/// element's `key`: a later element replaces the earlier one with the same key,
+/// new keys are appended. `None` if no layer sets `field`.
+pub fn merge_by_key(
+    layers: &[Box<dyn Source + Send + Sync>],
+    field: &str,
+    key: &str,
+) -> Result<Option<Value>, ConfigError> {
+    let mut merged: Option<Vec<Value>> = None;
+    for layer in layers {
+        let Some(items) = layer.collect()?.remove(field) else {
+            continue;
+        };
+        let items: Vec<Value> = items.try_deserialize()?;
+        let merged = merged.get_or_insert_with(Vec::new);
+        for item in items {
+            let id = item.get(key).cloned();
+            match merged
+                .iter_mut()
+                .find(|existing| id.is_some() && existing.get(key) == id.as_ref())
+            {
+                Some(existing) => *existing = item,
+                None => merged.push(item),
+            }
+        }
+    }
+    Ok(merged.map(Value::Array))
+}
+
+/// A config source with its top-level keys spelled as an alias (`server-port`,
+/// an explicit `alias`) renamed to the key they stand for. Layers spelling a key
+/// differently then merge as one key, later layer winning, instead of reaching
+/// deserialization as two. Within one layer the canonical spelling wins.
+#[derive(Debug, Clone)]
+pub struct CanonicalKeys {
+    pub source: Box<dyn Source + Send + Sync>,
+    /// `(alias, key)` pairs.
+    pub aliases: &'static [(&'static str, &'static str)],
+}
+
+impl Source for CanonicalKeys {
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, config::Value>, ConfigError> {
+        let mut table = self.source.collect()?;
+        for (alias, key) in self.aliases {
+            if let Some(value) = table.remove(*alias) {
+                table.entry(key.to_string()).or_insert(value);
+            }
+        }
+        Ok(table)
+    }
+}
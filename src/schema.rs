@@ -0,0 +1,152 @@
+//! Introspection metadata for a `ClapConfigFile` struct: every flag and config key
+//! it accepts. Returned by the generated `schema()` and printed by `--dump-schema`.
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+/// One `#[config_arg]` field. Internal fields (no `#[config_arg]`) are not listed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldSchema {
+    /// Rust field name.
+    pub field: &'static str,
+    /// `--long` flag name; `None` for config-only and positional fields.
+    pub long: Option<&'static str>,
+    pub short: Option<char>,
+    pub positional: bool,
+    /// Key in the config file; `None` for CLI-only fields.
+    pub config_key: Option<&'static str>,
+    /// Rust type as written, e.g. `Vec<String>`.
+    #[serde(rename = "type")]
+    pub ty: &'static str,
+    /// Declared `default_value` verbatim, or the evaluated `default_value_t`.
+    pub default: Option<String>,
+    pub help: String,
+    /// `"cli_only"`, `"config_only"` or `"cli_and_config"`.
+    pub availability: &'static str,
+}
+
+/// Render fields as a JSON document: `{"fields": [{...}, ...]}`.
+pub fn to_json(fields: &[FieldSchema]) -> String {
+    #[derive(Serialize)]
+    struct Document<'a> {
+        fields: &'a [FieldSchema],
+    }
+    serde_json::to_string_pretty(&Document { fields }).unwrap_or_default()
+}
+
+/// A `default_value_t` as `--help` and the schema show it: a string as is,
+/// anything else as JSON (`7`, `true`, `["a","b"]`).
+pub fn default_string<T: Serialize + ?Sized>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(Value::String(s)) => s,
+        Ok(other) => other.to_string(),
+        Err(_) => String::new(),
+    }
+}
+
+/// A JSON Schema (draft 07) object for the config file: each field with a config
+/// key becomes a property, typed from its Rust type, with its help text as
+/// `description` and its declared default. CLI-only fields are left out.
+pub fn json_schema(title: &str, fields: &[FieldSchema]) -> Value {
+    let mut properties = Map::new();
+    for f in fields {
+        let Some(key) = f.config_key else {
+            continue;
+        };
+        let mut property = type_schema(f.ty);
+        if !f.help.is_empty() {
+            property.insert("description".into(), Value::from(f.help.as_str()));
+        }
+        if let Some(default) = f
+            .default
+            .as_deref()
+            .and_then(|d| typed_default(&property, d))
+        {
+            property.insert("default".into(), default);
+        }
+        properties.insert(key.to_string(), Value::Object(property));
+    }
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": title,
+        "type": "object",
+        "properties": properties,
+    })
+}
+
+/// `{"type": ...}` for a Rust type as written in `FieldSchema::ty` (no spaces).
+/// Types it can't map (user structs and enums) get an empty, anything-goes schema.
+fn type_schema(ty: &str) -> Map<String, Value> {
+    let (outer, inner) = match ty.split_once('<') {
+        Some((outer, rest)) => (
+            outer.rsplit("::").next().unwrap_or(outer),
+            rest.strip_suffix('>'),
+        ),
+        None => (ty.rsplit("::").next().unwrap_or(ty), None),
+    };
+    let mut schema = Map::new();
+    match (outer, inner) {
+        ("Option", Some(inner)) => {
+            schema = type_schema(inner);
+            if let Some(Value::String(t)) = schema.get("type").cloned() {
+                schema.insert("type".into(), json!([t, "null"]));
+            }
+        }
+        ("Vec" | "HashSet" | "BTreeSet", Some(inner)) => {
+            schema.insert("type".into(), "array".into());
+            schema.insert("items".into(), Value::Object(type_schema(inner)));
+        }
+        ("HashMap" | "BTreeMap", Some(kv)) => {
+            schema.insert("type".into(), "object".into());
+            if let Some((_, v)) = kv.split_once(',') {
+                schema.insert("additionalProperties".into(), Value::Object(type_schema(v)));
+            }
+        }
+        ("bool", None) => {
+            schema.insert("type".into(), "boolean".into());
+        }
+        ("u8" | "u16" | "u32" | "u64" | "u128" | "usize", None) => {
+            schema.insert("type".into(), "integer".into());
+            schema.insert("minimum".into(), 0.into());
+        }
+        ("i8" | "i16" | "i32" | "i64" | "i128" | "isize", None) => {
+            schema.insert("type".into(), "integer".into());
+        }
+        ("f32" | "f64", None) => {
+            schema.insert("type".into(), "number".into());
+        }
+        ("String" | "PathBuf" | "char" | "Duration", None) => {
+            schema.insert("type".into(), "string".into());
+        }
+        _ => {}
+    }
+    schema
+}
+
+/// A `default_value` string as the JSON value its property type expects. `None`
+/// if it doesn't parse as that type (e.g. a `default_value_t` expression).
+fn typed_default(property: &Map<String, Value>, default: &str) -> Option<Value> {
+    let ty = match property.get("type") {
+        Some(Value::Array(types)) => types.first().and_then(Value::as_str),
+        other => other.and_then(Value::as_str),
+    };
+    match ty {
+        Some("boolean") => crate::boolish::parse_bool(default).ok().map(Value::from),
+        Some("integer" | "number") => serde_json::from_str::<Value>(default.trim())
+            .ok()
+            .filter(Value::is_number),
+        Some("array") => {
+            let empty = Map::new();
+            let items = property
+                .get("items")
+                .and_then(Value::as_object)
+                .unwrap_or(&empty);
+            default
+                .split(',')
+                .map(|item| typed_default(items, item.trim()))
+                .collect::<Option<Vec<_>>>()
+                .map(Value::Array)
+        }
+        _ => Some(Value::from(default)),
+    }
+}
@@ -0,0 +1,27 @@
+//! Navigating into a nested table of a config file, e.g. `[package.metadata.mytool]`
+//! in a `Cargo.toml`, so it can be used as the config source on its own.
+
+use config::{Config, ConfigError, File, FileFormat, Value};
+
+/// Parse `contents` and return the table at the dotted `path` as a standalone
+/// `Config` (which is itself a `config::Source`). `Ok(None)` if `path` is absent.
+pub fn extract(
+    contents: &str,
+    format: FileFormat,
+    path: &str,
+) -> Result<Option<Config>, ConfigError> {
+    let whole = Config::builder()
+        .add_source(File::from_str(contents, format))
+        .build()?;
+    let table = match whole.get::<Value>(path) {
+        Ok(value) => value.into_table()?,
+        Err(ConfigError::NotFound(_)) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+
+    let mut builder = Config::builder();
+    for (key, value) in table {
+        builder = builder.set_override(key, value)?;
+    }
+    builder.build().map(Some)
+}
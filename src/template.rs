@@ -0,0 +1,133 @@
+//! Starter config files: the generated `write_config_template` and
+//! `--generate-config` render one key per config field, with its help as a comment.
+
+use serde_json::Value;
+use std::borrow::Cow;
+use std::fmt::Write;
+
+/// One config key of a template.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateEntry {
+    /// A field's config key, or (owned) a key held by a `rest` field.
+    pub key: Cow<'static, str>,
+    /// Written as a comment above the key, where the format has comments.
+    pub help: String,
+    pub value: Value,
+}
+
+/// Render `entries` as a config file in `fmt` (`yaml`/`yml`, `toml`, `json`, `jsonc`/`json5`).
+///
+/// `null` values are written commented out (TOML), as `null` (YAML) or
+/// skipped inside nested tables. Errors name the unsupported format.
+pub fn render(fmt: &str, entries: &[TemplateEntry]) -> Result<String, String> {
+    let mut out = String::new();
+    match fmt {
+        "yaml" | "yml" => {
+            for e in entries {
+                comment(&mut out, "#", &e.help, "");
+                let _ = write!(out, "{}:", key(&e.key));
+                yaml_value(&mut out, &e.value, 1);
+            }
+        }
+        "toml" => {
+            for e in entries {
+                comment(&mut out, "#", &e.help, "");
+                if e.value.is_null() {
+                    let _ = writeln!(out, "# {} =", key(&e.key));
+                } else {
+                    let _ = writeln!(out, "{} = {}", key(&e.key), toml_value(&e.value));
+                }
+            }
+        }
+        "json" | "jsonc" | "json5" => {
+            let comments = fmt != "json";
+            out.push('{');
+            for (i, e) in entries.iter().enumerate() {
+                out.push_str(if i == 0 { "\n" } else { ",\n" });
+                if comments {
+                    comment(&mut out, "//", &e.help, "  ");
+                }
+                let value = serde_json::to_string_pretty(&e.value)
+                    .unwrap_or_default()
+                    .replace('\n', "\n  ");
+                let _ = write!(out, "  {}: {}", Value::from(e.key.as_ref()), value);
+            }
+            out.push_str("\n}\n");
+        }
+        other => return Err(format!("cannot write a {} config template", other)),
+    }
+    Ok(out)
+}
+
+fn comment(out: &mut String, marker: &str, help: &str, indent: &str) {
+    for line in help.lines().filter(|l| !l.trim().is_empty()) {
+        let _ = writeln!(out, "{}{} {}", indent, marker, line.trim());
+    }
+}
+
+fn is_bare_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// JSON string syntax is valid for YAML double-quoted and TOML basic strings alike.
+fn quoted(s: &str) -> String {
+    Value::from(s).to_string()
+}
+
+/// A YAML or TOML key, quoted unless it's a bare key in both.
+fn key(key: &str) -> String {
+    if is_bare_key(key) {
+        key.to_string()
+    } else {
+        quoted(key)
+    }
+}
+
+/// Appends ` value\n`, or a newline and indented block for a non-empty table.
+fn yaml_value(out: &mut String, value: &Value, depth: usize) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            out.push('\n');
+            for (k, v) in map {
+                let _ = write!(out, "{}{}:", "  ".repeat(depth), key(k));
+                yaml_value(out, v, depth + 1);
+            }
+        }
+        // JSON is valid YAML flow syntax
+        other => {
+            let _ = writeln!(out, " {}", other);
+        }
+    }
+}
+
+/// Inline TOML; nested tables become inline tables, `null`s inside them are dropped.
+fn toml_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => quoted(s),
+        Value::Array(items) => {
+            let items: Vec<String> = items
+                .iter()
+                .filter(|v| !v.is_null())
+                .map(toml_value)
+                .collect();
+            format!("[{}]", items.join(", "))
+        }
+        Value::Object(map) => {
+            let fields: Vec<String> = map
+                .iter()
+                .filter(|(_, v)| !v.is_null())
+                .map(|(k, v)| format!("{} = {}", key(k), toml_value(v)))
+                .collect();
+            if fields.is_empty() {
+                "{}".to_string()
+            } else {
+                format!("{{ {} }}", fields.join(", "))
+            }
+        }
+        other => other.to_string(),
+    }
+}
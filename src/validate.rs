@@ -0,0 +1,10 @@
+//! `#[config_validate]`: cross-field checks run on the merged config.
+
+/// Implement on a `#[config_validate]` struct to reject merged configs that are
+/// valid field by field but not as a whole (e.g. TLS enabled without a cert path).
+///
+/// Called by the generated `parse`, `parse_from` and `try_parse_from` after every
+/// source is merged; `parse_info` and friends don't run it.
+pub trait ValidateConfig {
+    fn validate(&self) -> Result<(), String>;
+}
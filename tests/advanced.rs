@@ -61,6 +61,24 @@ fn config_only_field_from_file() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn config_only_field_from_env_beats_file() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = TempDir::new()?;
+    std::fs::write(
+        dir.path().join("advanced-config.yaml"),
+        "special_secret: \"from_config\"\nextra_settings: { nesting_level: 3, allow_guest: false }",
+    )?;
+
+    Command::cargo_bin("advanced")?
+        .current_dir(dir.path())
+        .env("SPECIAL_SECRET", "from_env")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("special_secret: \"from_env\""));
+
+    Ok(())
+}
+
 #[test]
 fn cli_config_only_field_error() -> Result<(), Box<dyn std::error::Error>> {
     Command::cargo_bin("advanced")?
@@ -114,3 +132,390 @@ fn test_positional_args_cannot_override_cli_only() -> Result<(), Box<dyn std::er
 
     Ok(())
 }
+
+#[test]
+fn mutually_required_group_partial_errors() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = TempDir::new()?;
+    std::fs::write(
+        dir.path().join("advanced-config.yaml"),
+        "tls_cert: \"cert.pem\"\n",
+    )?;
+
+    Command::cargo_bin("advanced")?
+        .current_dir(dir.path())
+        .arg("--tls-key")
+        .arg("key.pem")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "tls-cert, tls-key, tls-ca must be provided together; missing: tls-ca",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn mutually_required_group_complete() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = TempDir::new()?;
+    std::fs::write(
+        dir.path().join("advanced-config.yaml"),
+        "tls_cert: \"cert.pem\"\ntls_ca: \"ca.pem\"\n",
+    )?;
+
+    Command::cargo_bin("advanced")?
+        .current_dir(dir.path())
+        .arg("--tls-key")
+        .arg("key.pem")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "tls_key: Some(\n        \"key.pem\"",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn requires_if_errors_when_condition_met() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = TempDir::new()?;
+
+    Command::cargo_bin("advanced")?
+        .current_dir(dir.path())
+        .args(["--mode", "remote"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "remote-url is required when mode is \"remote\"",
+        ));
+
+    Command::cargo_bin("advanced")?
+        .current_dir(dir.path())
+        .args(["--mode", "local"])
+        .assert()
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn requires_if_accepts_config_values() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = TempDir::new()?;
+    std::fs::write(
+        dir.path().join("advanced-config.yaml"),
+        "mode: remote
+remote_url: \"https://example.com\"\n",
+    )?;
+
+    Command::cargo_bin("advanced")?
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    Command::cargo_bin("advanced")?
+        .current_dir(dir.path())
+        .arg("--no-config")
+        .args(["--mode", "remote", "--remote-url", "https://example.com"])
+        .assert()
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn requires_rejects_flag_without_its_partner() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = TempDir::new()?;
+
+    Command::cargo_bin("advanced")?
+        .current_dir(dir.path())
+        .args(["--log-format", "json"])
+        .assert()
+        .failure()
+        .stderr(
+            predicate::str::contains("required arguments were not provided")
+                .and(predicate::str::contains("--log-file")),
+        );
+
+    Command::cargo_bin("advanced")?
+        .current_dir(dir.path())
+        .args(["--log-format", "json", "--log-file", "app.log"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"app.log\""));
+
+    Ok(())
+}
+
+#[test]
+fn deprecated_flag_warns_but_applies() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = TempDir::new()?;
+
+    Command::cargo_bin("advanced")?
+        .current_dir(dir.path())
+        .args(["--db", "sqlite://old.db"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Warning: --db is deprecated: use --database-url instead",
+        ))
+        .stdout(predicate::str::contains("\"sqlite://old.db\""));
+
+    std::fs::write(
+        dir.path().join("advanced-config.yaml"),
+        "db: sqlite://cfg.db\n",
+    )?;
+    Command::cargo_bin("advanced")?
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Warning: config key `db` is deprecated",
+        ));
+
+    // Any accepted spelling of the key warns
+    std::fs::write(
+        dir.path().join("advanced-config.yaml"),
+        "db_url: sqlite://cfg.db\n",
+    )?;
+    Command::cargo_bin("advanced")?
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Warning: config key `db` is deprecated",
+        ));
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn fifo_in_discovery_dir_does_not_hang() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = TempDir::new()?;
+    let status = Command::new("mkfifo")
+        .arg(dir.path().join("advanced-config.yaml"))
+        .status()?;
+    assert!(status.success());
+
+    // Nothing ever writes to the pipe, so reading it would block forever
+    assert_cmd::Command::cargo_bin("advanced")?
+        .current_dir(dir.path())
+        .timeout(std::time::Duration::from_secs(30))
+        .assert()
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn empty_config_file_errors() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = TempDir::new()?;
+    std::fs::write(dir.path().join("advanced-config.yaml"), "  \n\n")?;
+
+    Command::cargo_bin("advanced")?
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("is empty"));
+
+    Ok(())
+}
+
+#[test]
+fn help_from_const() -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("advanced")?
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Items added to the list from the config file",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn dump_schema_lists_flags_and_config_keys() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = TempDir::new()?;
+    let output = Command::cargo_bin("advanced")?
+        .current_dir(dir.path())
+        .arg("--dump-schema")
+        .output()?;
+    assert!(output.status.success());
+
+    let schema: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    let fields = schema["fields"].as_array().unwrap();
+    let field = |name: &str| {
+        fields
+            .iter()
+            .find(|f| f["field"] == name)
+            .unwrap_or_else(|| panic!("{} missing from schema", name))
+    };
+
+    let port = field("server_port");
+    assert_eq!(port["long"], "port");
+    assert_eq!(port["config_key"], "port");
+    assert_eq!(port["type"], "u16");
+    assert_eq!(port["default"], "8080");
+    assert_eq!(port["help"], "Port to run the server on");
+    assert_eq!(port["availability"], "cli_and_config");
+
+    let secret = field("special_secret");
+    assert_eq!(secret["availability"], "config_only");
+    assert!(secret["long"].is_null());
+
+    assert_eq!(field("paths")["positional"], true);
+    assert!(fields.iter().all(|f| f["field"] != "internal_config"));
+
+    Ok(())
+}
+
+#[test]
+fn config_file_dash_reads_stdin() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = TempDir::new()?;
+
+    assert_cmd::Command::cargo_bin("advanced")?
+        .current_dir(dir.path())
+        .args(["--config-file", "-", "--config-format", "json"])
+        .write_stdin(r#"{"database_url": "sqlite://piped.db"}"#)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"sqlite://piped.db\""));
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn unreadable_discovered_config_errors() -> Result<(), Box<dyn std::error::Error>> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = TempDir::new()?;
+    let path = dir.path().join("advanced-config.yaml");
+    std::fs::write(&path, "database_url: \"sqlite://secret.db\"\n")?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o000))?;
+    if std::fs::read(&path).is_ok() {
+        // Running as root: permissions don't stop reads
+        return Ok(());
+    }
+
+    Command::cargo_bin("advanced")?
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(
+            predicate::str::contains("failed to read config file")
+                .and(predicate::str::contains("advanced-config.yaml")),
+        );
+
+    Ok(())
+}
+
+#[test]
+fn explicit_help_overrides_doc_comment() -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("advanced")?
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Enable verbose debug output")
+                .and(predicate::str::contains("replaced by the explicit help").not()),
+        );
+
+    Ok(())
+}
+
+#[test]
+fn generate_config_prints_defaults_and_exits() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = TempDir::new()?;
+    std::fs::write(dir.path().join("advanced-config.yaml"), "port: 1\n")?;
+
+    Command::cargo_bin("advanced")?
+        .current_dir(dir.path())
+        .arg("--generate-config=toml")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("# Port to run the server on\nport = 8080").and(
+                predicate::str::contains("extra_settings = { nesting_level = 0 }"),
+            ),
+        );
+
+    Command::cargo_bin("advanced")?
+        .current_dir(dir.path())
+        .arg("--generate-config")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("port: 8080"));
+
+    Ok(())
+}
+
+#[test]
+fn dump_env_prints_effective_config_with_secrets_masked() -> Result<(), Box<dyn std::error::Error>>
+{
+    let dir = TempDir::new()?;
+    std::fs::write(
+        dir.path().join("advanced-config.yaml"),
+        "special_secret: hunter2\nextend_list: [a, b]\nextra_settings: { nesting_level: 3 }\n",
+    )?;
+
+    Command::cargo_bin("advanced")?
+        .current_dir(dir.path())
+        .args(["--dump-env", "--debug"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("PORT=8080\n")
+                .and(predicate::str::contains("DEBUG=true\n"))
+                .and(predicate::str::contains("EXTEND_LIST=a,b\n"))
+                .and(predicate::str::contains("SPECIAL_SECRET=********\n"))
+                .and(predicate::str::contains("hunter2").not())
+                // Not the Debug print of a normal run
+                .and(predicate::str::contains("AdvancedConfig").not()),
+        );
+
+    Command::cargo_bin("advanced")?
+        .current_dir(dir.path())
+        .args(["--dump-env", "--unsafe"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("SPECIAL_SECRET=hunter2\n"));
+
+    Ok(())
+}
+
+#[test]
+fn value_enum_from_cli_and_config() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = TempDir::new()?;
+
+    Command::cargo_bin("advanced")?
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("log_level: Info"));
+
+    std::fs::write(dir.path().join("advanced-config.yaml"), "log-level: warn\n")?;
+    Command::cargo_bin("advanced")?
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("log_level: Warn"));
+
+    Command::cargo_bin("advanced")?
+        .current_dir(dir.path())
+        .args(["--log-level", "debug"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("log_level: Debug"));
+
+    Command::cargo_bin("advanced")?
+        .current_dir(dir.path())
+        .args(["--log-level", "loud"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "possible values: error, warn, info, debug",
+        ));
+
+    Ok(())
+}
@@ -0,0 +1,683 @@
+use clap_config_file::{ClapConfigError, ClapConfigFile, MemoryFs};
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct CliSetConfig {
+    #[config_arg()]
+    pub port: u16,
+
+    #[config_arg()]
+    pub host: String,
+}
+
+#[test]
+fn was_cli_set_tracks_cli_overrides() {
+    let fs = MemoryFs::new("/project").file("app.yaml", "port: 1\nhost: h\n");
+
+    let (_, _, _, details) =
+        CliSetConfig::try_parse_info_with_details_fs(["app", "--port", "2"], &fs).unwrap();
+    assert!(details.was_cli_set("port"));
+    assert!(!details.was_cli_set("host"));
+
+    let (_, _, _, details) = CliSetConfig::try_parse_info_with_details_fs(["app"], &fs).unwrap();
+    assert!(!details.was_cli_set("port"));
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct PairsConfig {
+    #[config_arg(num_args = 2, value_names = ["KEY", "VALUE"])]
+    pub define: std::collections::HashMap<String, String>,
+
+    #[config_arg()]
+    pub label: std::collections::BTreeMap<String, u32>,
+}
+
+#[test]
+fn key_value_pairs_merge_with_config_map() {
+    let fs = MemoryFs::new("/project").file(
+        "app.yaml",
+        "define:\n  FROM_CONFIG: a\n  SHARED: config\nlabel:\n  x: 1\n",
+    );
+
+    let (cfg, _, _) = PairsConfig::parse_info_with_fs(
+        [
+            "app", "--define", "SHARED", "cli", "--define", "NEW", "b", "--label", "y=2",
+        ],
+        &fs,
+    );
+
+    assert_eq!(cfg.define.len(), 3);
+    assert_eq!(cfg.define["FROM_CONFIG"], "a");
+    assert_eq!(cfg.define["SHARED"], "cli");
+    assert_eq!(cfg.define["NEW"], "b");
+    assert_eq!(
+        cfg.label.into_iter().collect::<Vec<_>>(),
+        [("x".into(), 1), ("y".into(), 2)]
+    );
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+#[config_file_examples = "app --port 8080"]
+#[config_file_examples = "app --config-file ci.yaml\napp --no-config --port 1"]
+struct ExamplesConfig {
+    #[config_arg()]
+    pub port: u16,
+}
+
+#[test]
+fn examples_are_listed_at_the_end_of_help() {
+    let help = ExamplesConfig::try_parse_info_with_fs(["app", "--help"], &MemoryFs::new("/"))
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(
+        help.ends_with(
+            "Examples:\n  app --port 8080\n  app --config-file ci.yaml\n  app --no-config --port 1\n"
+        ),
+        "{}",
+        help
+    );
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct ActionConfig {
+    #[config_arg(short = 'D', action = "append")]
+    pub define: Vec<String>,
+
+    #[config_arg(positional)]
+    pub files: Vec<String>,
+}
+
+#[test]
+fn explicit_append_takes_one_value_per_occurrence() {
+    let fs = MemoryFs::new("/project").file("app.yaml", "define: [from_config]\n");
+
+    let (cfg, _, _) =
+        ActionConfig::parse_info_with_fs(["app", "-D", "foo", "-D", "bar", "input.txt"], &fs);
+
+    assert_eq!(cfg.define, vec!["from_config", "foo", "bar"]);
+    assert_eq!(cfg.files, vec!["input.txt"]);
+}
+
+#[derive(ClapConfigFile)]
+struct IndexedPositionalsConfig {
+    #[config_arg(positional, index = 2)]
+    pub destination: String,
+
+    #[config_arg(positional, index = 1)]
+    pub source: String,
+}
+
+#[test]
+fn positional_index_decouples_cli_order_from_field_order() {
+    let (cfg, _, _) =
+        IndexedPositionalsConfig::parse_info_with_fs(["cp", "a.txt", "b.txt"], &MemoryFs::new("/"));
+
+    assert_eq!(cfg.source, "a.txt");
+    assert_eq!(cfg.destination, "b.txt");
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct TrueDefaultConfig {
+    #[config_arg(default_value = "true")]
+    pub color: bool,
+
+    #[config_arg()]
+    pub debug: bool,
+
+    #[config_arg(accept_from = "cli_only", default_value = "true")]
+    pub progress: bool,
+}
+
+#[test]
+fn true_bools_can_be_turned_off_from_cli() {
+    let fs = MemoryFs::new("/project").file("app.yaml", "debug: true\n");
+
+    let (cfg, _, _) = TrueDefaultConfig::parse_info_with_fs(["app"], &fs);
+    assert!(cfg.color);
+    assert!(cfg.debug);
+    assert!(cfg.progress);
+
+    let (cfg, _, _) = TrueDefaultConfig::parse_info_with_fs(
+        ["app", "--color=false", "--debug=false", "--progress=false"],
+        &fs,
+    );
+    assert!(!cfg.color);
+    assert!(!cfg.debug);
+    assert!(!cfg.progress);
+}
+
+#[test]
+fn bare_bool_flag_still_means_true() {
+    let (cfg, _, _) =
+        TrueDefaultConfig::parse_info_with_fs(["app", "--debug"], &MemoryFs::new("/"));
+    assert!(cfg.debug);
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct ToArgsConfig {
+    #[config_arg(name = "listen-port", short = 'p')]
+    pub port: u16,
+
+    #[config_arg(default_value = "true")]
+    pub color: bool,
+
+    #[config_arg()]
+    pub verbose: bool,
+
+    #[config_arg()]
+    pub tags: Vec<String>,
+
+    #[config_arg()]
+    pub region: Option<String>,
+
+    #[config_arg()]
+    pub labels: std::collections::BTreeMap<String, String>,
+
+    #[config_arg()]
+    pub hosts: Vec<String>,
+
+    #[config_arg(accept_from = "config_only")]
+    pub secret: String,
+
+    #[config_arg(positional)]
+    pub inputs: Vec<String>,
+}
+
+#[test]
+fn to_args_round_trips_through_parse() {
+    let fs = MemoryFs::new("/project").file(
+        "app.yaml",
+        "listen-port: 8080\ncolor: false\ntags: [a, b]\nregion: eu\nlabels: {env: prod}\nhosts: [h1]\nsecret: s3cret\n",
+    );
+    let (cfg, _, _) = ToArgsConfig::parse_info_with_fs(["app", "--verbose", "x.txt"], &fs);
+
+    let args = cfg.to_args();
+    assert!(!args.iter().any(|a| a.contains("secret")));
+
+    let mut argv = vec!["app".to_string(), "--no-config".to_string()];
+    argv.extend(args);
+    let (reparsed, _, _) = ToArgsConfig::parse_info_with_fs(argv, &MemoryFs::new("/"));
+
+    assert_eq!(reparsed.port, 8080);
+    assert!(!reparsed.color);
+    assert!(reparsed.verbose);
+    assert_eq!(reparsed.tags, vec!["a", "b"]);
+    assert_eq!(reparsed.region.as_deref(), Some("eu"));
+    assert_eq!(reparsed.labels.get("env").map(String::as_str), Some("prod"));
+    assert_eq!(reparsed.hosts, vec!["h1"]);
+    assert_eq!(reparsed.secret, "");
+    assert_eq!(reparsed.inputs, vec!["x.txt"]);
+}
+
+fn lowercase(s: String) -> String {
+    s.to_lowercase()
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct MappedConfig {
+    #[config_arg(map = lowercase)]
+    pub host: String,
+
+    #[config_arg(map = lowercase, trim)]
+    pub region: String,
+
+    #[config_arg(map = |p: u16| p.max(1024))]
+    pub port: u16,
+}
+
+#[test]
+fn map_normalizes_value_from_any_source() {
+    let fs = MemoryFs::new("/project").file(
+        "app.yaml",
+        "host: Example.COM\nregion: \" EU-West \"\nport: 80\n",
+    );
+
+    let (cfg, _, _) = MappedConfig::parse_info_with_fs(["app"], &fs);
+    assert_eq!(cfg.host, "example.com");
+    assert_eq!(cfg.region, "eu-west");
+    assert_eq!(cfg.port, 1024);
+
+    let (cfg, _, _) = MappedConfig::parse_info_with_fs(["app", "--host", "API.Local"], &fs);
+    assert_eq!(cfg.host, "api.local");
+}
+
+#[test]
+fn generate_completion_covers_fields_and_builtin_flags() {
+    let mut buf = Vec::new();
+    CliSetConfig::generate_completion(clap_config_file::clap_complete::Shell::Bash, &mut buf);
+    let script = String::from_utf8(buf).unwrap();
+
+    assert!(script.contains("--port"));
+    assert!(script.contains("--host"));
+    assert!(script.contains("--config-file"));
+    assert!(script.contains("--no-config"));
+}
+
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct Limits {
+    pub max_depth: u32,
+    pub label: Option<String>,
+    pub strict: bool,
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct FlattenConfig {
+    #[config_arg(flatten(max_depth = "u32", label = "Option<String>", strict = "bool"))]
+    pub limits: Limits,
+}
+
+#[test]
+fn flatten_dotted_flags_override_nested_config() {
+    let fs = MemoryFs::new("/project").file(
+        "app.yaml",
+        "limits:\n  max_depth: 3\n  label: cfg\n  strict: false\n",
+    );
+
+    let (cfg, _, _) = FlattenConfig::parse_info_with_fs(["app"], &fs);
+    assert_eq!(cfg.limits.max_depth, 3);
+    assert_eq!(cfg.limits.label.as_deref(), Some("cfg"));
+
+    let (cfg, _, _) = FlattenConfig::parse_info_with_fs(
+        ["app", "--limits.max-depth", "7", "--limits.strict"],
+        &fs,
+    );
+    assert_eq!(cfg.limits.max_depth, 7);
+    assert_eq!(cfg.limits.label.as_deref(), Some("cfg"));
+    assert!(cfg.limits.strict);
+
+    let (cfg, _, _) =
+        FlattenConfig::parse_info_with_fs(["app", "--limits.label", "cli"], &MemoryFs::new("/"));
+    assert_eq!(cfg.limits.max_depth, 0);
+    assert_eq!(cfg.limits.label.as_deref(), Some("cli"));
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct AliasConfig {
+    #[config_arg(alias = "db-url", alias = "database")]
+    pub database_url: String,
+}
+
+#[test]
+fn aliases_work_as_flags_and_config_keys() {
+    let fs = MemoryFs::new("/");
+    for flag in ["--database-url", "--db-url", "--database"] {
+        let (cfg, _, _) = AliasConfig::parse_info_with_fs(["app", flag, "sqlite://x"], &fs);
+        assert_eq!(cfg.database_url, "sqlite://x", "{}", flag);
+    }
+
+    let fs = MemoryFs::new("/project").file("app.yaml", "database: sqlite://cfg\n");
+    let (cfg, _, _) = AliasConfig::parse_info_with_fs(["app"], &fs);
+    assert_eq!(cfg.database_url, "sqlite://cfg");
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct SpellingsConfig {
+    #[config_arg(
+        short = 'p',
+        long = "port",
+        visible_alias = "server-port",
+        alias = "listen-port",
+        help = "Port to listen on"
+    )]
+    pub http_port: u16,
+}
+
+#[test]
+fn short_long_aliases_and_help_compose() {
+    let fs = MemoryFs::new("/");
+    for flag in ["-p", "--port", "--server-port", "--listen-port"] {
+        let (cfg, _, _) = SpellingsConfig::parse_info_with_fs(["app", flag, "8080"], &fs);
+        assert_eq!(cfg.http_port, 8080, "{}", flag);
+    }
+
+    let help = SpellingsConfig::try_parse_info_with_fs(["app", "--help"], &fs)
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(help.contains("-p, --port <HTTP_PORT>"), "{}", help);
+    assert!(help.contains("Port to listen on"), "{}", help);
+    assert!(help.contains("--server-port"), "{}", help);
+    assert!(!help.contains("--listen-port"), "{}", help);
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+#[config_auto_short]
+struct AutoShortConfig {
+    #[config_arg()]
+    pub port: u16,
+
+    #[config_arg(short = 'p')]
+    pub profile: String,
+
+    #[config_arg()]
+    pub host: String,
+}
+
+#[test]
+fn auto_short_takes_the_first_free_letter() {
+    let fs = MemoryFs::new("/");
+    let (cfg, _, _) = AutoShortConfig::parse_info_with_fs(
+        ["app", "-o", "80", "-p", "dev", "-s", "example.com"],
+        &fs,
+    );
+    assert_eq!(cfg.port, 80);
+    assert_eq!(cfg.profile, "dev");
+    assert_eq!(cfg.host, "example.com");
+}
+
+// Running out of letters is a compile-time `deprecated` warning naming the flag
+#[allow(deprecated)]
+mod crowded {
+    use clap_config_file::ClapConfigFile;
+
+    #[derive(ClapConfigFile)]
+    #[config_file_name = "app"]
+    #[config_auto_short]
+    pub struct CrowdedConfig {
+        #[config_arg()]
+        pub a: u8,
+
+        #[config_arg()]
+        pub aa: u8,
+    }
+}
+
+#[test]
+fn auto_short_leaves_a_flag_without_a_free_letter_long_only() {
+    let fs = MemoryFs::new("/");
+    let (cfg, _, _) =
+        crowded::CrowdedConfig::parse_info_with_fs(["app", "-a", "1", "--aa", "2"], &fs);
+    assert_eq!((cfg.a, cfg.aa), (1, 2));
+
+    let help = crowded::CrowdedConfig::try_parse_info_with_fs(["app", "--help"], &fs)
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(help.contains("-a, --a <A>"), "{}", help);
+    assert!(help.contains("    --aa <AA>"), "{}", help);
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct DocHelpConfig {
+    /// Port to listen on,
+    /// wrapped over two lines.
+    ///
+    /// Ports below 1024 need root.
+    #[config_arg()]
+    pub port: u16,
+
+    /// Host name
+    #[config_arg()]
+    pub host: String,
+}
+
+#[test]
+fn doc_comments_become_short_and_long_help() {
+    let help = |flag: &str| {
+        DocHelpConfig::try_parse_info_with_fs(["app", flag], &MemoryFs::new("/"))
+            .err()
+            .unwrap()
+            .to_string()
+    };
+
+    let short = help("-h");
+    assert!(
+        short.contains("Port to listen on, wrapped over two lines."),
+        "{}",
+        short
+    );
+    assert!(!short.contains("need root"), "{}", short);
+    assert!(short.contains("Host name"), "{}", short);
+
+    let long = help("--help");
+    assert!(
+        long.contains("Port to listen on, wrapped over two lines."),
+        "{}",
+        long
+    );
+    assert!(long.contains("Ports below 1024 need root."), "{}", long);
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct ConflictsConfig {
+    #[config_arg(conflicts_with = "json-output,verbose")]
+    pub quiet: bool,
+
+    #[config_arg()]
+    pub json_output: bool,
+
+    #[config_arg(short = 'v')]
+    pub verbose: bool,
+}
+
+#[test]
+fn conflicts_with_rejects_flags_given_together() {
+    let fs = MemoryFs::new("/project");
+
+    for args in [
+        ["app", "--quiet", "--json-output"],
+        ["app", "-v", "--quiet"],
+    ] {
+        let err = ConflictsConfig::try_parse_info_with_fs(args, &fs)
+            .err()
+            .unwrap();
+        assert!(
+            matches!(err, clap_config_file::ClapConfigError::Cli(ref e)
+                if e.kind() == clap::error::ErrorKind::ArgumentConflict),
+            "{}",
+            err
+        );
+    }
+}
+
+#[test]
+fn conflicts_with_ignores_config_values() {
+    let fs = MemoryFs::new("/project").file("app.yaml", "json_output: true\n");
+
+    let (cfg, _, _) = ConflictsConfig::parse_info_with_fs(["app", "--quiet"], &fs);
+    assert!(cfg.quiet && cfg.json_output);
+}
+
+#[derive(ClapConfigFile)]
+struct RequireEqualsConfig {
+    #[config_arg(require_equals)]
+    pub profile: String,
+
+    #[config_arg(positional)]
+    pub files: Vec<String>,
+}
+
+#[test]
+fn require_equals_rejects_separate_value() {
+    let fs = MemoryFs::new("/");
+
+    let (cfg, _, _) =
+        RequireEqualsConfig::parse_info_with_fs(["app", "--profile=dev", "a.txt"], &fs);
+    assert_eq!(cfg.profile, "dev");
+    assert_eq!(cfg.files, ["a.txt"]);
+
+    let err = RequireEqualsConfig::try_parse_info_with_fs(["app", "--profile", "dev"], &fs)
+        .err()
+        .unwrap();
+    assert!(matches!(err, clap_config_file::ClapConfigError::Cli(_)));
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct TrailingConfig {
+    #[config_arg()]
+    pub verbose: bool,
+
+    #[config_arg(positional)]
+    pub script: Option<String>,
+
+    #[config_arg(trailing)]
+    pub rest: Vec<String>,
+}
+
+#[test]
+fn trailing_takes_everything_after_double_dash() {
+    let (cfg, _, _) = TrailingConfig::parse_info_with_fs(
+        [
+            "app",
+            "--verbose",
+            "run.sh",
+            "--",
+            "--verbose",
+            "-x",
+            "plain",
+        ],
+        &MemoryFs::new("/"),
+    );
+
+    assert!(cfg.verbose);
+    assert_eq!(cfg.script.as_deref(), Some("run.sh"));
+    assert_eq!(cfg.rest, vec!["--verbose", "-x", "plain"]);
+}
+
+#[test]
+fn trailing_round_trips_through_to_args() {
+    let (cfg, _, _) =
+        TrailingConfig::parse_info_with_fs(["app", "run.sh", "--", "-x"], &MemoryFs::new("/"));
+
+    let args = cfg.to_args();
+    assert_eq!(args, ["run.sh", "--", "-x"]);
+
+    let mut argv = vec!["app".to_string()];
+    argv.extend(args);
+    let (reparsed, _, _) = TrailingConfig::parse_info_with_fs(argv, &MemoryFs::new("/"));
+    assert_eq!(reparsed.script.as_deref(), Some("run.sh"));
+    assert_eq!(reparsed.rest, vec!["-x"]);
+}
+
+#[test]
+fn parse_info_from_customized_command() {
+    let cmd = || {
+        AliasConfig::command()
+            .version("1.2.3")
+            .about("Talks to a database")
+    };
+    let fs = MemoryFs::new("/");
+
+    let err = AliasConfig::try_parse_info_from_command_with_fs(cmd(), ["app", "--version"], &fs)
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("1.2.3"), "{}", err);
+
+    let err = AliasConfig::try_parse_info_from_command_with_fs(cmd(), ["app", "--help"], &fs)
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("Talks to a database"), "{}", err);
+
+    let (cfg, _, _) = AliasConfig::try_parse_info_from_command_with_fs(
+        cmd(),
+        ["app", "--db-url", "sqlite://x"],
+        &fs,
+    )
+    .unwrap();
+    assert_eq!(cfg.database_url, "sqlite://x");
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct HiddenConfig {
+    #[config_arg()]
+    pub port: u16,
+
+    /// Experimental: may change without notice
+    #[config_arg(hide)]
+    pub turbo_mode: bool,
+}
+
+#[test]
+fn hidden_flag_works_but_is_not_in_help() {
+    let fs = MemoryFs::new("/project").file("app.yaml", "turbo_mode: true\n");
+    let (cfg, _, _) = HiddenConfig::parse_info_with_fs(["app"], &fs);
+    assert!(cfg.turbo_mode);
+
+    let (cfg, _, _) =
+        HiddenConfig::parse_info_with_fs(["app", "--turbo-mode"], &MemoryFs::new("/"));
+    assert!(cfg.turbo_mode);
+
+    let help = HiddenConfig::try_parse_info_with_fs(["app", "--help"], &MemoryFs::new("/"))
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(help.contains("--port"), "{}", help);
+    assert!(!help.contains("turbo"), "{}", help);
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct VerbosityConfig {
+    #[config_arg(short = 'v', count)]
+    pub verbose: u8,
+
+    #[config_arg(count)]
+    pub quiet: u32,
+}
+
+#[test]
+fn count_flags_add_up_and_override_config() {
+    let fs = MemoryFs::new("/project").file("app.yaml", "verbose: 1\nquiet: 2\n");
+
+    let (cfg, _, _, details) =
+        VerbosityConfig::try_parse_info_with_details_fs(["app", "-vvv"], &fs).unwrap();
+    assert_eq!(cfg.verbose, 3);
+    assert_eq!(cfg.quiet, 2);
+    assert!(details.was_cli_set("verbose"));
+    assert!(!details.was_cli_set("quiet"));
+
+    let (cfg, _, _) = VerbosityConfig::parse_info_with_fs(["app"], &fs);
+    assert_eq!(cfg.verbose, 1);
+
+    let (cfg, _, _) =
+        VerbosityConfig::parse_info_with_fs(["app", "-v", "--verbose"], &MemoryFs::new("/"));
+    assert_eq!(cfg.verbose, 2);
+    assert_eq!(cfg.to_args(), ["--verbose", "--verbose"]);
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct NegatableConfig {
+    #[config_arg(negatable)]
+    pub debug: bool,
+
+    #[config_arg(negatable, accept_from = "cli_only", default_value = "true")]
+    pub color: bool,
+}
+
+#[test]
+fn no_flag_overrides_a_true_config_value() {
+    let fs = MemoryFs::new("/project").file("app.yaml", "debug: true\n");
+
+    let (cfg, _, _) = NegatableConfig::parse_info_with_fs(["app"], &fs);
+    assert!(cfg.debug);
+    assert!(cfg.color);
+
+    let (cfg, _, _, details) =
+        NegatableConfig::try_parse_info_with_details_fs(["app", "--no-debug", "--no-color"], &fs)
+            .unwrap();
+    assert!(!cfg.debug);
+    assert!(!cfg.color);
+    assert!(details.was_cli_set("debug"));
+    assert_eq!(cfg.to_args(), ["--no-color"]);
+
+    let err = NegatableConfig::try_parse_info_with_fs(["app", "--debug", "--no-debug"], &fs)
+        .err()
+        .unwrap();
+    assert!(matches!(err, ClapConfigError::Cli(_)), "{}", err);
+}
@@ -0,0 +1,6 @@
+// Macro misuse that must be rejected at compile time, with the error pointing at the culprit
+#[test]
+fn compile_fail() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
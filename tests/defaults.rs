@@ -0,0 +1,105 @@
+use clap_config_file::{ClapConfigFile, MemoryFs};
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct DefaultsConfig {
+    #[config_arg(default_value = "8080")]
+    pub port: u16,
+
+    #[config_arg(default_value = "localhost")]
+    pub host: String,
+
+    #[config_arg(accept_from = "config_only", default_value = "3")]
+    pub retries: u32,
+}
+
+#[test]
+fn config_omitting_field_yields_declared_default() {
+    let fs = MemoryFs::new("/project").file("app.yaml", "host: example.com\n");
+
+    let (cfg, _, _) = DefaultsConfig::parse_info_with_fs(["app"], &fs);
+
+    assert_eq!(cfg.port, 8080);
+    assert_eq!(cfg.host, "example.com");
+    assert_eq!(cfg.retries, 3);
+}
+
+#[test]
+fn declared_default_does_not_mask_config() {
+    let fs = MemoryFs::new("/project").file("app.yaml", "port: 9000\n");
+
+    let (cfg, _, _) = DefaultsConfig::parse_info_with_fs(["app"], &fs);
+    assert_eq!(cfg.port, 9000);
+
+    let (cfg, _, _) = DefaultsConfig::parse_info_with_fs(["app", "--port", "1"], &fs);
+    assert_eq!(cfg.port, 1);
+}
+
+#[test]
+fn declared_default_without_any_config() {
+    let (cfg, _, _) = DefaultsConfig::parse_info_with_fs(["app"], &MemoryFs::new("/"));
+
+    assert_eq!(cfg.port, 8080);
+    assert_eq!(cfg.host, "localhost");
+}
+
+const DEFAULT_WORKERS: usize = 4;
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct TypedDefaultsConfig {
+    #[config_arg(default_value_t = DEFAULT_WORKERS)]
+    pub workers: usize,
+
+    #[config_arg(accept_from = "config_only", default_value_t = 1.5)]
+    pub backoff: f64,
+
+    #[config_arg(accept_from = "cli_only", default_value_t = String::from("text"))]
+    pub output: String,
+}
+
+#[test]
+fn default_value_t_seeds_every_layer() {
+    let (cfg, _, _) = TypedDefaultsConfig::parse_info_with_fs(["app"], &MemoryFs::new("/"));
+
+    assert_eq!(cfg.workers, DEFAULT_WORKERS);
+    assert_eq!(cfg.backoff, 1.5);
+    assert_eq!(cfg.output, "text");
+}
+
+#[test]
+fn default_value_t_does_not_mask_config_or_cli() {
+    let fs = MemoryFs::new("/project").file("app.yaml", "workers: 8\nbackoff: 0.5\n");
+
+    let (cfg, _, _) = TypedDefaultsConfig::parse_info_with_fs(["app", "--output", "json"], &fs);
+
+    assert_eq!(cfg.workers, 8);
+    assert_eq!(cfg.backoff, 0.5);
+    assert_eq!(cfg.output, "json");
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct EvaluatedDefaultsConfig {
+    /// Seconds to wait
+    #[config_arg(default_value_t = std::time::Duration::from_secs(5))]
+    pub timeout: std::time::Duration,
+
+    #[config_arg(default_value_t = 3 + 4)]
+    pub retries: u32,
+}
+
+#[test]
+fn default_value_t_is_shown_evaluated() {
+    let help = EvaluatedDefaultsConfig::command().render_help().to_string();
+    assert!(
+        help.contains("Seconds to wait [default: 5000ms]"),
+        "{}",
+        help
+    );
+    assert!(help.contains("[default: 7]"), "{}", help);
+
+    let schema = EvaluatedDefaultsConfig::schema();
+    assert_eq!(schema[0].default.as_deref(), Some("5000ms"));
+    assert_eq!(schema[1].default.as_deref(), Some("7"));
+}
@@ -0,0 +1,135 @@
+use clap_config_file::{config_path_for, find_config, ClapConfigError, ClapConfigFile, MemoryFs};
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+// The only test in this binary that changes the process's current directory
+#[test]
+fn config_path_for_walks_up_from_current_dir() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = TempDir::new()?;
+    let sub = dir.path().join("a/b");
+    std::fs::create_dir_all(&sub)?;
+    std::fs::write(dir.path().join("a/tool.toml"), "port = 1\n")?;
+    std::env::set_current_dir(&sub)?;
+
+    let found = config_path_for("tool", &["yaml", "toml"])?.unwrap();
+    assert_eq!(
+        found.canonicalize()?,
+        dir.path().join("a/tool.toml").canonicalize()?
+    );
+    assert!(config_path_for("missing", &["yaml", "toml"])?.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn find_config_follows_merge_rules() {
+    let fs = MemoryFs::new("/project/sub")
+        .file("/project/app.yaml", "port: 1\n")
+        .file("/project/sub/app.yaml", "port: 2\n");
+
+    let err = find_config(&fs, &["app"], &["yaml"], &[], false, false).unwrap_err();
+    assert!(matches!(err, ClapConfigError::MultipleConfigFiles(_)));
+
+    let layered = find_config(&fs, &["app"], &["yaml"], &[], false, true).unwrap();
+    assert_eq!(
+        layered,
+        vec![
+            PathBuf::from("/project/app.yaml"),
+            PathBuf::from("/project/sub/app.yaml")
+        ]
+    );
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "myapp,.myapprc"]
+struct MultiNameConfig {
+    #[config_arg()]
+    pub port: u16,
+}
+
+#[test]
+fn earlier_base_name_wins_within_a_directory() {
+    let fs = MemoryFs::new("/project")
+        .file("myapp.yaml", "port: 1\n")
+        .file(".myapprc.yaml", "port: 2\n");
+    let (cfg, path, _) = MultiNameConfig::parse_info_with_fs(["app"], &fs);
+    assert_eq!(cfg.port, 1);
+    assert_eq!(path, Some(PathBuf::from("/project/myapp.yaml")));
+
+    let fs = MemoryFs::new("/project").file(".myapprc.yaml", "port: 2\n");
+    let (cfg, _, _) = MultiNameConfig::parse_info_with_fs(["app"], &fs);
+    assert_eq!(cfg.port, 2);
+}
+
+#[test]
+fn base_names_in_different_directories_still_conflict() {
+    let fs = MemoryFs::new("/project/sub")
+        .file("/project/myapp.yaml", "port: 1\n")
+        .file("/project/sub/.myapprc.yaml", "port: 2\n");
+
+    let err = find_config(&fs, &["myapp", ".myapprc"], &["yaml"], &[], false, false).unwrap_err();
+    assert!(matches!(err, ClapConfigError::MultipleConfigFiles(_)));
+}
+
+#[test]
+fn find_upwards_returns_nearest_match() {
+    let fs = MemoryFs::new("/ws/crate/src")
+        .file("/ws/Cargo.toml", "")
+        .file("/ws/crate/Cargo.toml", "");
+
+    assert_eq!(
+        clap_config_file::find_upwards(&fs, "Cargo.toml"),
+        Some(PathBuf::from("/ws/crate/Cargo.toml"))
+    );
+    assert_eq!(clap_config_file::find_upwards(&fs, "missing.toml"), None);
+}
+
+#[test]
+fn expand_path_handles_escapes_and_bare_dollars() {
+    use clap_config_file::expand_path;
+    use std::path::Path;
+
+    std::env::set_var("CCF_TEST_EXPAND_DIR", "conf");
+    let fs = MemoryFs::new("/");
+    let expand = |p: &str| expand_path(Path::new(p), &fs);
+
+    assert_eq!(
+        expand("/a/$CCF_TEST_EXPAND_DIR/b.yaml"),
+        Ok(PathBuf::from("/a/conf/b.yaml"))
+    );
+    assert_eq!(expand("/a/$$x/$/b"), Ok(PathBuf::from("/a/$x/$/b")));
+    assert!(expand("/a/${CCF_TEST_EXPAND_DIR").is_err());
+    // No home directory to expand against
+    assert!(expand("~/app.yaml").is_err());
+    assert_eq!(expand("/a~b"), Ok(PathBuf::from("/a~b")));
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+#[config_file_stop_at = ".git, .hg"]
+struct ProjectBoundConfig {
+    #[config_arg()]
+    pub port: u16,
+}
+
+#[test]
+fn stop_at_bounds_discovery_to_the_project() {
+    // `.git` is a directory, present only through the files below it
+    let fs = MemoryFs::new("/home/me/project/sub")
+        .file("/home/me/app.yaml", "port: 1\n")
+        .file("/home/me/project/.git/HEAD", "ref: refs/heads/main\n");
+    let (cfg, path, _) = ProjectBoundConfig::parse_info_with_fs(["app"], &fs);
+    assert_eq!(cfg.port, 0);
+    assert!(path.is_none());
+
+    // The marker's own directory is still searched
+    let fs = fs.file("/home/me/project/app.yaml", "port: 2\n");
+    let (cfg, _, _) = ProjectBoundConfig::parse_info_with_fs(["app"], &fs);
+    assert_eq!(cfg.port, 2);
+
+    // Whichever marker comes first on the way up
+    let fs = fs.file("/home/me/project/sub/.hg", "");
+    let (cfg, path, _) = ProjectBoundConfig::parse_info_with_fs(["app"], &fs);
+    assert_eq!(cfg.port, 0);
+    assert!(path.is_none());
+}
@@ -0,0 +1,231 @@
+use clap_config_file::{ClapConfigFile, MemoryFs};
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct EnvConfig {
+    #[config_arg(env = "CCF_TEST_DATABASE_URL,CCF_TEST_DB_URL")]
+    pub database_url: String,
+
+    #[config_arg(env = "CCF_TEST_PORT")]
+    pub port: u16,
+}
+
+#[test]
+fn legacy_env_candidate_is_used() {
+    std::env::set_var("CCF_TEST_DB_URL", "postgres://legacy");
+    let fs = MemoryFs::new("/project").file("app.yaml", "database_url: from-config\n");
+
+    let (cfg, _, _) = EnvConfig::parse_info_with_fs(["app"], &fs);
+
+    assert_eq!(cfg.database_url, "postgres://legacy");
+}
+
+#[test]
+fn env_is_parsed_and_below_cli() {
+    std::env::set_var("CCF_TEST_PORT", "9090");
+    let fs = MemoryFs::new("/project").file("app.yaml", "port: 1\n");
+
+    let (cfg, _, _) = EnvConfig::parse_info_with_fs(["app"], &fs);
+    assert_eq!(cfg.port, 9090);
+
+    let (cfg, _, _) = EnvConfig::parse_info_with_fs(["app", "--port", "2"], &fs);
+    assert_eq!(cfg.port, 2);
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct SecretConfig {
+    #[config_arg(accept_from = "config_only", env = "CCF_TEST_SPECIAL_SECRET")]
+    pub special_secret: String,
+}
+
+#[test]
+fn config_only_field_reads_env_without_a_flag() {
+    let fs = MemoryFs::new("/project").file("app.yaml", "special_secret: from-file\n");
+
+    let (cfg, _, _) = SecretConfig::parse_info_with_fs(["app"], &fs);
+    assert_eq!(cfg.special_secret, "from-file");
+
+    std::env::set_var("CCF_TEST_SPECIAL_SECRET", "from-env");
+    let (cfg, _, _) = SecretConfig::parse_info_with_fs(["app"], &fs);
+    assert_eq!(cfg.special_secret, "from-env");
+    std::env::remove_var("CCF_TEST_SPECIAL_SECRET");
+
+    let err = SecretConfig::try_parse_info_with_fs(["app", "--special-secret", "x"], &fs)
+        .err()
+        .unwrap();
+    assert!(matches!(err, clap_config_file::ClapConfigError::Cli(_)));
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct LabelsConfig {
+    #[config_arg(env = "CCF_TEST_LABELS")]
+    pub labels: std::collections::HashMap<String, String>,
+
+    #[config_arg(accept_from = "config_only", env = "CCF_TEST_LIMITS")]
+    pub limits: std::collections::BTreeMap<String, u32>,
+}
+
+#[test]
+fn map_env_entries_sit_between_config_and_cli() {
+    let fs = MemoryFs::new("/project").file(
+        "app.yaml",
+        "labels:\n  team: core\n  tier: gold\nlimits:\n  cpu: 1\n",
+    );
+
+    std::env::set_var("CCF_TEST_LABELS", "tier=silver, zone=eu");
+    std::env::set_var("CCF_TEST_LIMITS", "mem=512");
+    let parsed = LabelsConfig::try_parse_info_with_fs(["app", "--labels", "zone=us"], &fs);
+    std::env::set_var("CCF_TEST_LIMITS", "mem");
+    let bad = LabelsConfig::try_parse_info_with_fs(["app"], &fs);
+    std::env::remove_var("CCF_TEST_LABELS");
+    std::env::remove_var("CCF_TEST_LIMITS");
+
+    let (cfg, _, _) = parsed.unwrap();
+    assert_eq!(cfg.labels.len(), 3);
+    assert_eq!(cfg.labels["team"], "core");
+    assert_eq!(cfg.labels["tier"], "silver");
+    assert_eq!(cfg.labels["zone"], "us");
+    assert_eq!(
+        cfg.limits.into_iter().collect::<Vec<_>>(),
+        [("cpu".to_string(), 1), ("mem".to_string(), 512)]
+    );
+    let err = bad.err().unwrap();
+    assert!(
+        err.to_string()
+            .contains("CCF_TEST_LIMITS expects KEY=VALUE"),
+        "{}",
+        err
+    );
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct TrimConfig {
+    #[config_arg(trim, env = "CCF_TEST_API_TOKEN")]
+    pub api_token: String,
+
+    #[config_arg(trim)]
+    pub region: Option<String>,
+
+    #[config_arg(trim)]
+    pub hosts: Vec<String>,
+
+    #[config_arg()]
+    pub motd: String,
+}
+
+#[test]
+fn trim_strips_padding_from_every_source() {
+    std::env::set_var("CCF_TEST_API_TOKEN", "  s3cret\n");
+    let fs = MemoryFs::new("/project").file(
+        "app.yaml",
+        "region: \" eu-west \"\nhosts: [\" a \"]\nmotd: \" hi \"\n",
+    );
+
+    let (cfg, _, _) = TrimConfig::parse_info_with_fs(["app", "--hosts", "b  "], &fs);
+
+    assert_eq!(cfg.api_token, "s3cret");
+    assert_eq!(cfg.region.as_deref(), Some("eu-west"));
+    assert_eq!(cfg.hosts, vec!["a", "b"]);
+    assert_eq!(cfg.motd, " hi ");
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct TypedEnvConfig {
+    /// Verbose logging
+    #[config_arg(env = "CCF_TEST_VERBOSE")]
+    pub verbose: bool,
+
+    #[config_arg(env = "CCF_TEST_RATIO", default_value = "0.5")]
+    pub ratio: f64,
+}
+
+#[test]
+fn env_values_are_parsed_with_from_str() {
+    std::env::set_var("CCF_TEST_VERBOSE", "true");
+    std::env::set_var("CCF_TEST_RATIO", "0.25");
+
+    let (cfg, _, _) = TypedEnvConfig::parse_info_with_fs(["app"], &MemoryFs::new("/"));
+
+    assert!(cfg.verbose);
+    assert_eq!(cfg.ratio, 0.25);
+}
+
+#[test]
+fn help_shows_env_vars_and_precedence() {
+    let help = TypedEnvConfig::try_parse_info_with_fs(["app", "--help"], &MemoryFs::new("/"))
+        .err()
+        .unwrap()
+        .to_string();
+
+    assert!(
+        help.contains("Verbose logging [env: CCF_TEST_VERBOSE]"),
+        "{}",
+        help
+    );
+    assert!(
+        help.contains("[env: CCF_TEST_RATIO] [default: 0.5]"),
+        "{}",
+        help
+    );
+    assert!(
+        help.contains("Precedence: command line > env > config file > default"),
+        "{}",
+        help
+    );
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+#[config_file_env_ignore_empty]
+struct IgnoreEmptyConfig {
+    #[config_arg(env = "CCF_TEST_EMPTY_PORT")]
+    pub port: u16,
+
+    #[config_arg(env = "CCF_TEST_EMPTY_NAME")]
+    pub name: String,
+}
+
+#[test]
+fn empty_env_var_falls_through_to_config() {
+    std::env::set_var("CCF_TEST_EMPTY_PORT", "");
+    std::env::set_var("CCF_TEST_EMPTY_NAME", "");
+    let fs = MemoryFs::new("/project").file("app.yaml", "port: 8080\nname: from-config\n");
+
+    let (cfg, _, _) = IgnoreEmptyConfig::parse_info_with_fs(["app"], &fs);
+
+    assert_eq!(cfg.port, 8080);
+    assert_eq!(cfg.name, "from-config");
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct BoolishEnvConfig {
+    #[config_arg(env = "CCF_TEST_DEBUG")]
+    pub debug: bool,
+
+    #[config_arg(env = "CCF_TEST_COLOR")]
+    pub color: Option<bool>,
+}
+
+#[test]
+fn env_bools_accept_boolish_spellings() {
+    let fs = MemoryFs::new("/");
+    std::env::set_var("CCF_TEST_DEBUG", "1");
+    std::env::set_var("CCF_TEST_COLOR", "Off");
+
+    let (cfg, _, _) = BoolishEnvConfig::parse_info_with_fs(["app"], &fs);
+    assert!(cfg.debug);
+    assert_eq!(cfg.color, Some(false));
+
+    std::env::set_var("CCF_TEST_DEBUG", "maybe");
+    let err = BoolishEnvConfig::try_parse_info_with_fs(["app"], &fs)
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("CCF_TEST_DEBUG"), "{}", err);
+    std::env::remove_var("CCF_TEST_DEBUG");
+    std::env::remove_var("CCF_TEST_COLOR");
+}
@@ -0,0 +1,334 @@
+use clap_config_file::{ClapConfigError, ClapConfigFile, ConfigFs, MemoryFs};
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+#[config_file_formats = "yaml,json"]
+struct AppConfig {
+    #[config_arg()]
+    pub port: u16,
+}
+
+#[test]
+fn multiple_config_files_in_one_dir() {
+    let fs = MemoryFs::new("/project")
+        .file("app.yaml", "port: 1\n")
+        .file("app.json", r#"{"port": 2}"#);
+
+    match AppConfig::try_parse_info_with_fs(["app"], &fs) {
+        Err(ClapConfigError::MultipleConfigFiles(paths)) => assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("/project/app.yaml"),
+                PathBuf::from("/project/app.json")
+            ]
+        ),
+        other => panic!("unexpected: {:?}", other.map(|r| r.1)),
+    }
+}
+
+#[test]
+fn multiple_config_files_walking_up() {
+    let fs = MemoryFs::new("/project/sub")
+        .file("/project/app.yaml", "port: 1\n")
+        .file("/project/sub/app.yaml", "port: 2\n");
+
+    let err = AppConfig::try_parse_info_with_fs(["app"], &fs)
+        .err()
+        .unwrap();
+    assert!(matches!(err, ClapConfigError::MultipleConfigFiles(ref p) if p.len() == 2));
+}
+
+#[test]
+fn missing_explicit_config_file() {
+    let err = AppConfig::try_parse_info_with_fs(
+        ["app", "--config-file", "/nope.yaml"],
+        &MemoryFs::new("/"),
+    )
+    .err()
+    .unwrap();
+
+    match err {
+        ClapConfigError::ConfigReadError { path, .. } => {
+            assert_eq!(path, PathBuf::from("/nope.yaml"))
+        }
+        other => panic!("unexpected: {}", other),
+    }
+}
+
+/// A `MemoryFs` whose files all exist but can't be read, like a permissions problem
+struct UnreadableFs(MemoryFs);
+
+impl ConfigFs for UnreadableFs {
+    fn current_dir(&self) -> io::Result<PathBuf> {
+        self.0.current_dir()
+    }
+    fn is_file(&self, path: &Path) -> bool {
+        self.0.is_file(path)
+    }
+    fn read_to_string(&self, _: &Path) -> io::Result<String> {
+        Err(io::Error::from(io::ErrorKind::PermissionDenied))
+    }
+    fn read_dir(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        self.0.read_dir(dir)
+    }
+}
+
+#[test]
+fn unreadable_discovered_config_file() {
+    let fs = UnreadableFs(MemoryFs::new("/project").file("app.yaml", "port: 1\n"));
+
+    match AppConfig::try_parse_info_with_fs(["app"], &fs) {
+        Err(ClapConfigError::ConfigReadError { path, source }) => {
+            assert_eq!(path, PathBuf::from("/project/app.yaml"));
+            assert_eq!(source.kind(), io::ErrorKind::PermissionDenied);
+        }
+        other => panic!("unexpected: {:?}", other.map(|r| r.1)),
+    }
+}
+
+/// A filesystem that panics on read, standing in for a panic deep inside loading
+struct PanickingFs(MemoryFs);
+
+impl ConfigFs for PanickingFs {
+    fn current_dir(&self) -> io::Result<PathBuf> {
+        self.0.current_dir()
+    }
+    fn is_file(&self, path: &Path) -> bool {
+        self.0.is_file(path)
+    }
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        panic!("cannot read {}", path.display())
+    }
+    fn read_dir(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        self.0.read_dir(dir)
+    }
+}
+
+#[test]
+fn parse_info_safe_turns_a_panic_into_an_error() {
+    let fs = PanickingFs(MemoryFs::new("/project").file(
+        "app.yaml", "port: 1
+",
+    ));
+
+    match AppConfig::parse_info_safe_with_fs(["app"], &fs) {
+        Err(ClapConfigError::Panicked(msg)) => assert_eq!(msg, "cannot read /project/app.yaml"),
+        other => panic!("unexpected: {:?}", other.map(|r| r.1)),
+    }
+
+    let fs = MemoryFs::new("/project").file("app.yaml", "port: 1\n");
+    let (cfg, _, _) = AppConfig::parse_info_safe_with_fs(["app"], &fs).unwrap();
+    assert_eq!(cfg.port, 1);
+}
+
+#[test]
+fn config_value_of_wrong_type() {
+    let fs = MemoryFs::new("/project").file("app.yaml", "port: not-a-number\n");
+
+    let err = AppConfig::try_parse_info_with_fs(["app"], &fs)
+        .err()
+        .unwrap();
+    assert!(matches!(err, ClapConfigError::ConfigDeserializeError(_)));
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+#[config_deny_unknown]
+struct StrictKeysConfig {
+    #[config_arg()]
+    pub port: u16,
+}
+
+#[test]
+fn deny_unknown_rejects_misspelled_keys() {
+    let fs = MemoryFs::new("/project").file(
+        "app.yaml", "port: 1
+",
+    );
+    let (cfg, _, _) = StrictKeysConfig::try_parse_info_with_fs(["app"], &fs).unwrap();
+    assert_eq!(cfg.port, 1);
+
+    let fs = MemoryFs::new("/project").file(
+        "app.yaml", "prot: 1
+",
+    );
+    let err = StrictKeysConfig::try_parse_info_with_fs(["app"], &fs)
+        .err()
+        .unwrap();
+    assert!(matches!(err, ClapConfigError::ConfigDeserializeError(_)));
+    assert!(err.to_string().contains("prot"), "{}", err);
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+#[config_on_error = "default"]
+struct LenientConfig {
+    #[config_arg(default_value = "8080")]
+    pub port: u16,
+
+    #[config_arg()]
+    pub host: String,
+}
+
+#[test]
+fn config_on_error_default_falls_back_to_defaults() {
+    let fs = MemoryFs::new("/project").file(
+        "app.yaml",
+        "port: not-a-number
+host: h
+",
+    );
+
+    let (cfg, _, _) = LenientConfig::try_parse_info_with_fs(["app", "--host", "cli"], &fs).unwrap();
+    assert_eq!(cfg.port, 8080);
+    assert_eq!(cfg.host, "cli");
+}
+
+#[test]
+fn unknown_explicit_format() {
+    let fs = MemoryFs::new("/project").file("settings.ini", "port = 1\n");
+
+    let err = AppConfig::try_parse_info_with_fs(["app", "--config-file", "settings.ini"], &fs)
+        .err()
+        .unwrap();
+    assert!(
+        matches!(err, ClapConfigError::FormatGuessFailed(p) if p.as_os_str() == "settings.ini")
+    );
+}
+
+#[test]
+fn bad_cli_arg() {
+    let err = AppConfig::try_parse_info_with_fs(["app", "--port", "x"], &MemoryFs::new("/"))
+        .err()
+        .unwrap();
+    assert!(matches!(err, ClapConfigError::Cli(_)));
+}
+
+#[test]
+fn try_parse_from_returns_usage_errors() {
+    match AppConfig::try_parse_from(["app", "--no-such-flag"]) {
+        Err(ClapConfigError::Cli(e)) => {
+            assert_eq!(e.kind(), clap::error::ErrorKind::UnknownArgument)
+        }
+        other => panic!("unexpected: {:?}", other.map(|c| c.port)),
+    }
+}
+
+#[test]
+fn try_parse_from_returns_requested_output_instead_of_exiting() {
+    let fs = MemoryFs::new("/project");
+    match AppConfig::try_parse_info_with_fs(["app", "--generate-config=json"], &fs) {
+        Err(ClapConfigError::Display(out)) => assert!(out.contains("\"port\""), "{}", out),
+        other => panic!("unexpected: {:?}", other.map(|r| r.1)),
+    }
+    match AppConfig::try_parse_from(["app", "--dump-schema"]) {
+        Err(ClapConfigError::Display(out)) => assert!(out.contains("\"fields\""), "{}", out),
+        other => panic!("unexpected: {:?}", other.map(|c| c.port)),
+    }
+}
+
+#[derive(ClapConfigFile)]
+struct EnvConfig {
+    #[config_arg(env = "CCF_TEST_ERRORS_PORT")]
+    pub port: u16,
+}
+
+#[test]
+fn unparsable_env_value() {
+    std::env::set_var("CCF_TEST_ERRORS_PORT", "eighty");
+
+    let err = EnvConfig::try_parse_info_with_fs(["app"], &MemoryFs::new("/"))
+        .err()
+        .unwrap();
+    match err {
+        ClapConfigError::Invalid(msg) => assert!(msg.contains("CCF_TEST_ERRORS_PORT"), "{}", msg),
+        other => panic!("unexpected: {}", other),
+    }
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct RequiredConfig {
+    #[config_arg(required, env = "CCF_TEST_REQUIRED_DB_URL")]
+    pub database_url: String,
+}
+
+#[test]
+fn missing_required_field() {
+    let fs = MemoryFs::new("/project");
+
+    match RequiredConfig::try_parse_info_with_fs(["app"], &fs) {
+        Err(ClapConfigError::Invalid(msg)) => assert_eq!(
+            msg,
+            "missing required field `database-url`; set it with --database-url or \
+             $CCF_TEST_REQUIRED_DB_URL or config key `database_url`"
+        ),
+        other => panic!("unexpected: {:?}", other.map(|r| r.1)),
+    }
+}
+
+#[test]
+fn required_field_from_any_source() {
+    let fs = MemoryFs::new("/project");
+    let (cfg, _, _) =
+        RequiredConfig::parse_info_with_fs(["app", "--database-url", "sqlite://cli"], &fs);
+    assert_eq!(cfg.database_url, "sqlite://cli");
+
+    let fs = fs.file("app.yaml", "database_url: sqlite://cfg\n");
+    let (cfg, _, _) = RequiredConfig::parse_info_with_fs(["app"], &fs);
+    assert_eq!(cfg.database_url, "sqlite://cfg");
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+#[config_validate]
+struct TlsConfig {
+    #[config_arg()]
+    pub tls: bool,
+
+    #[config_arg()]
+    pub cert_path: Option<String>,
+}
+
+impl clap_config_file::ValidateConfig for TlsConfig {
+    fn validate(&self) -> Result<(), String> {
+        if self.tls && self.cert_path.is_none() {
+            return Err("--tls needs --cert-path".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn config_validate_runs_in_parse_only() {
+    let err = TlsConfig::try_parse_from(["app", "--no-config", "--tls"])
+        .err()
+        .unwrap();
+    assert!(
+        matches!(&err, ClapConfigError::Invalid(msg) if msg == "--tls needs --cert-path"),
+        "{}",
+        err
+    );
+
+    let cfg =
+        TlsConfig::try_parse_from(["app", "--no-config", "--tls", "--cert-path", "c.pem"]).unwrap();
+    assert_eq!(cfg.cert_path.as_deref(), Some("c.pem"));
+
+    // parse_info returns the merged config as is
+    let (cfg, _, _) = TlsConfig::parse_info_with_fs(["app", "--tls"], &MemoryFs::new("/"));
+    assert!(cfg.tls);
+}
+
+#[cfg(not(feature = "http"))]
+#[test]
+fn config_file_url_needs_http_feature() {
+    let err = AppConfig::try_parse_info_with_fs(
+        ["app", "--config-file", "https://example.com/app.yaml"],
+        &MemoryFs::new("/"),
+    )
+    .err()
+    .unwrap();
+    assert!(err.to_string().contains("`http` feature"), "{}", err);
+}
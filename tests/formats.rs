@@ -0,0 +1,451 @@
+use clap_config_file::{ClapConfigFile, MemoryFs};
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+#[config_file_formats = "jsonc"]
+struct JsoncConfig {
+    #[config_arg()]
+    pub port: u16,
+
+    #[config_arg()]
+    pub hosts: Vec<String>,
+}
+
+#[test]
+fn jsonc_allows_comments_and_trailing_commas() {
+    let fs = MemoryFs::new("/project").file(
+        "app.jsonc",
+        r#"{
+            // the port to listen on
+            "port": 8080,
+            /* block comments work too */
+            "hosts": ["a", "b",],
+        }"#,
+    );
+
+    let (cfg, _, fmt) = JsoncConfig::parse_info_with_fs(["app"], &fs);
+
+    assert_eq!(cfg.port, 8080);
+    assert_eq!(cfg.hosts, vec!["a", "b"]);
+    assert_eq!(fmt, Some("jsonc"));
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+#[config_file_formats = "yaml,toml"]
+struct InlineConfig {
+    #[config_arg()]
+    pub port: u16,
+
+    #[config_arg()]
+    pub hosts: Vec<String>,
+
+    #[config_arg()]
+    pub name: String,
+}
+
+#[test]
+fn inline_config_multiline_yaml() {
+    let raw = "port: 8080\nhosts:\n  - a\n  - b\n";
+
+    let (cfg, path, fmt) =
+        InlineConfig::parse_info_with_fs(["app", "--config", raw], &MemoryFs::new("/"));
+
+    assert_eq!(cfg.port, 8080);
+    assert_eq!(cfg.hosts, vec!["a", "b"]);
+    assert!(path.is_none());
+    assert_eq!(fmt, Some("yaml"));
+}
+
+#[test]
+fn inline_config_multiline_toml_layers_over_file() {
+    let fs = MemoryFs::new("/project").file("app.yaml", "port: 1\nname: from-file\n");
+    let raw = "port = 2\nhosts = [\n  \"x\",\n]\n";
+
+    let (cfg, _, fmt) = InlineConfig::parse_info_with_fs(["app", "--config", raw], &fs);
+
+    assert_eq!(cfg.port, 2);
+    assert_eq!(cfg.hosts, vec!["x"]);
+    assert_eq!(cfg.name, "from-file");
+    assert_eq!(fmt, Some("yaml"));
+}
+
+#[test]
+fn inline_config_format_can_be_named() {
+    let fs = MemoryFs::new("/");
+
+    let (cfg, _, fmt) = InlineConfig::parse_info_with_fs(
+        ["app", "--config-format", "toml", "--config", "port = 5"],
+        &fs,
+    );
+    assert_eq!(cfg.port, 5);
+    assert_eq!(fmt, Some("toml"));
+
+    // No fallback to the other formats when the named one doesn't parse
+    let err = InlineConfig::try_parse_info_with_fs(
+        ["app", "--config-format", "yaml", "--config", "port = 5"],
+        &fs,
+    )
+    .err()
+    .unwrap();
+    assert_eq!(err.to_string(), "--config is not valid yaml");
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+#[config_file_formats = "toml,json"]
+struct LayeredConfig {
+    #[config_arg()]
+    pub port: u16,
+
+    #[config_arg()]
+    pub name: String,
+}
+
+#[test]
+fn repeated_config_files_layer_with_independent_formats() {
+    let fs = MemoryFs::new("/project")
+        .file("base.toml", "port = 1\nname = \"base\"\n")
+        .file("override.json", r#"{"port": 2}"#);
+
+    let (cfg, path, fmt) = LayeredConfig::parse_info_with_fs(
+        [
+            "app",
+            "--config-file",
+            "base.toml",
+            "--config-file",
+            "override.json",
+        ],
+        &fs,
+    );
+
+    assert_eq!(cfg.port, 2);
+    assert_eq!(cfg.name, "base");
+    assert_eq!(path, Some(std::path::PathBuf::from("override.json")));
+    assert_eq!(fmt, Some("json"));
+}
+
+#[test]
+fn config_file_format_override_suffix() {
+    let fs = MemoryFs::new("/project").file("settings.conf", r#"{"port": 7}"#);
+
+    let (cfg, path, fmt) =
+        LayeredConfig::parse_info_with_fs(["app", "--config-file", "settings.conf:json"], &fs);
+
+    assert_eq!(cfg.port, 7);
+    assert_eq!(path, Some(std::path::PathBuf::from("settings.conf")));
+    assert_eq!(fmt, Some("json"));
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+#[config_file_formats = "ini,ron"]
+struct IniRonConfig {
+    #[config_arg()]
+    pub port: u16,
+
+    #[config_arg()]
+    pub name: String,
+}
+
+#[test]
+fn ini_files_are_parsed_as_ini() {
+    let fs = MemoryFs::new("/project").file("app.ini", "port = 8080\nname = from-ini\n");
+
+    let (cfg, _, fmt) = IniRonConfig::parse_info_with_fs(["app"], &fs);
+
+    assert_eq!(cfg.port, 8080);
+    assert_eq!(cfg.name, "from-ini");
+    assert_eq!(fmt, Some("ini"));
+}
+
+#[test]
+fn ron_files_are_parsed_as_ron() {
+    let fs = MemoryFs::new("/project").file("app.ron", r#"(port: 9090, name: "from-ron")"#);
+
+    let (cfg, _, fmt) = IniRonConfig::parse_info_with_fs(["app"], &fs);
+
+    assert_eq!(cfg.port, 9090);
+    assert_eq!(cfg.name, "from-ron");
+    assert_eq!(fmt, Some("ron"));
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct RawConfig {
+    #[config_arg()]
+    pub port: u16,
+
+    #[config_arg()]
+    pub timeout: u32,
+}
+
+#[test]
+fn raw_merged_config_exposes_unknown_keys() {
+    // `timout` is a typo: the field silently stays at its default
+    let fs = MemoryFs::new("/project").file("app.yaml", "port: 8080\ntimout: 30\n");
+
+    let (cfg, _, _, raw) =
+        RawConfig::parse_info_with_raw_fs(["app", "--config", "port: 9090"], &fs);
+
+    assert_eq!(cfg.timeout, 0);
+    assert_eq!(raw["port"], 9090);
+    assert_eq!(raw["timout"], 30);
+}
+
+#[test]
+fn runtime_format_helpers_match_generated_behavior() {
+    use clap_config_file::format::{detect_format, file_format, guess_format, split_format};
+    use std::path::{Path, PathBuf};
+
+    let known = &["yaml", "json", "toml"];
+    assert_eq!(guess_format(Path::new("app.YAML"), known), Some("yaml"));
+    assert_eq!(guess_format(Path::new("app.ini"), known), None);
+    assert_eq!(guess_format(Path::new("app"), known), None);
+
+    assert_eq!(
+        split_format(Path::new("base.conf:TOML"), known),
+        (PathBuf::from("base.conf"), Some("toml"))
+    );
+    // Not a known format: the colon is part of the path
+    assert_eq!(
+        split_format(Path::new("c:odd.json"), known),
+        (PathBuf::from("c:odd.json"), Some("json"))
+    );
+
+    assert!(file_format("jsonc").is_some());
+    assert!(file_format("xml").is_none());
+
+    assert_eq!(
+        detect_format("port = 1", known).map(|(f, _)| f),
+        Some("toml")
+    );
+    assert_eq!(
+        detect_format("port: 1", known).map(|(f, _)| f),
+        Some("yaml")
+    );
+    assert!(detect_format("just text", known).is_none());
+}
+
+#[test]
+fn config_file_dash_reads_stdin() {
+    let fs = MemoryFs::new("/project")
+        .file("app.yaml", "port: 1\nname: file\n")
+        .stdin("port = 2\n");
+
+    let (cfg, path, format) = InlineConfig::parse_info_with_fs(
+        ["app", "--config-file", "-", "--config-format", "toml"],
+        &fs,
+    );
+    assert_eq!(cfg.port, 2);
+    assert!(path.is_none());
+    assert_eq!(format, Some("toml"));
+
+    // Defaults to the first declared format
+    let fs = fs.stdin("port: 3\n");
+    let (cfg, _, format) = InlineConfig::parse_info_with_fs(["app", "--config-file", "-"], &fs);
+    assert_eq!(cfg.port, 3);
+    assert_eq!(format, Some("yaml"));
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+#[config_file_formats = "yaml,toml,jsonc"]
+struct TemplateConfig {
+    /// Port to listen on
+    #[config_arg(default_value = "8080")]
+    pub port: u16,
+
+    #[config_arg()]
+    pub hosts: Vec<String>,
+
+    #[config_arg()]
+    pub label: Option<String>,
+
+    #[config_arg(accept_from = "cli_only")]
+    pub verbose: bool,
+}
+
+#[test]
+fn config_template_round_trips_in_each_format() {
+    let (cfg, _, _) = TemplateConfig::parse_info_with_fs(
+        ["app", "--port", "9000", "--hosts", "a", "b"],
+        &MemoryFs::new("/"),
+    );
+
+    for fmt in ["yaml", "toml", "jsonc"] {
+        let mut buf = Vec::new();
+        cfg.write_config_template(fmt, &mut buf).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert!(text.contains("Port to listen on"), "{}:\n{}", fmt, text);
+        assert!(!text.contains("verbose"), "{}:\n{}", fmt, text);
+
+        let fs = MemoryFs::new("/project").file(format!("app.{}", fmt), text.as_str());
+        let (back, _, _) = TemplateConfig::parse_info_with_fs(["app"], &fs);
+        assert_eq!(back.port, 9000, "{}:\n{}", fmt, text);
+        assert_eq!(back.hosts, vec!["a", "b"], "{}:\n{}", fmt, text);
+        assert_eq!(back.label, None, "{}:\n{}", fmt, text);
+    }
+
+    let err = cfg
+        .write_config_template("ini", &mut Vec::new())
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "my-tool"]
+#[config_file_formats = "yaml,toml"]
+#[config_file_wrap_key = "self"]
+struct WrappedConfig {
+    #[config_arg()]
+    pub port: u16,
+
+    #[config_arg()]
+    pub host: String,
+}
+
+#[test]
+fn wrap_key_reads_config_nested_under_program_name() {
+    let fs = MemoryFs::new("/project").file(
+        "my-tool.toml",
+        "[other-tool]\nport = 1\n\n[my-tool]\nport = 8080\nhost = \"example.com\"\n",
+    );
+
+    let (cfg, _, _) = WrappedConfig::parse_info_with_fs(["app"], &fs);
+    assert_eq!(cfg.port, 8080);
+    assert_eq!(cfg.host, "example.com");
+}
+
+#[test]
+fn wrap_key_falls_back_to_unwrapped_file() {
+    let fs = MemoryFs::new("/project").file("my-tool.yaml", "port: 9000\n");
+
+    let (cfg, _, _) = WrappedConfig::parse_info_with_fs(["app"], &fs);
+    assert_eq!(cfg.port, 9000);
+}
+
+#[cfg(windows)]
+#[test]
+fn format_detection_handles_windows_paths() {
+    use clap_config_file::format::{guess_format, split_format};
+    use std::path::{Path, PathBuf};
+
+    let fmts = ["yaml", "toml"];
+    assert_eq!(
+        guess_format(Path::new(r"C:\Users\me\App.YAML"), &fmts),
+        Some("yaml")
+    );
+    assert_eq!(
+        split_format(Path::new(r"C:\etc\app.yaml"), &fmts),
+        (PathBuf::from(r"C:\etc\app.yaml"), Some("yaml"))
+    );
+    assert_eq!(
+        split_format(Path::new(r"C:\etc\app.conf:toml"), &fmts),
+        (PathBuf::from(r"C:\etc\app.conf"), Some("toml"))
+    );
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = ".apprc"]
+#[config_file_formats = "yaml,toml"]
+struct DotfileConfig {
+    #[config_arg()]
+    pub port: u16,
+}
+
+#[test]
+fn extensionless_dotfile_is_discovered_and_sniffed() {
+    let fs = MemoryFs::new("/project").file(".apprc", "port = 9000\n");
+
+    let (cfg, path, fmt) = DotfileConfig::parse_info_with_fs(["app"], &fs);
+
+    assert_eq!(cfg.port, 9000);
+    assert_eq!(path, Some(std::path::PathBuf::from("/project/.apprc")));
+    assert_eq!(fmt, Some("toml"));
+}
+
+#[test]
+fn extensionless_config_file_tries_declared_formats_in_order() {
+    let fs = MemoryFs::new("/project").file("settings", "port: 7000\n");
+
+    let (cfg, _, fmt) = InlineConfig::parse_info_with_fs(["app", "--config-file", "settings"], &fs);
+
+    assert_eq!(cfg.port, 7000);
+    assert_eq!(fmt, Some("yaml"));
+
+    let fs = MemoryFs::new("/project").file("settings", "not a table");
+    let err = InlineConfig::try_parse_info_with_fs(["app", "--config-file", "settings"], &fs)
+        .err()
+        .unwrap();
+    assert!(matches!(
+        err,
+        clap_config_file::ClapConfigError::FormatGuessFailed(_)
+    ));
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct CaseConfig {
+    #[config_arg()]
+    pub server_port: u16,
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+#[config_case = "kebab"]
+struct KebabConfig {
+    #[config_arg(default_value = "1")]
+    pub server_port: u16,
+}
+
+#[test]
+fn config_keys_accept_snake_and_kebab_case() {
+    for text in ["server_port: 8080\n", "server-port: 8080\n"] {
+        let fs = MemoryFs::new("/project").file("app.yaml", text);
+
+        let (cfg, _, _) = CaseConfig::parse_info_with_fs(["app"], &fs);
+        assert_eq!(cfg.server_port, 8080, "{}", text);
+
+        let (cfg, _, _) = KebabConfig::parse_info_with_fs(["app"], &fs);
+        assert_eq!(cfg.server_port, 8080, "{}", text);
+    }
+}
+
+#[test]
+fn layers_spelling_a_key_differently_merge_as_one_key() {
+    let fs = MemoryFs::new("/project")
+        .file(
+            "base.yaml",
+            "server_port: 1
+",
+        )
+        .file(
+            "local.yaml",
+            "server-port: 2
+",
+        );
+    let args = [
+        "app",
+        "--config-file",
+        "base.yaml",
+        "--config-file",
+        "local.yaml",
+    ];
+
+    let (cfg, _, _) = CaseConfig::try_parse_info_with_fs(args, &fs).unwrap();
+    assert_eq!(cfg.server_port, 2);
+
+    let (cfg, _, _) = KebabConfig::try_parse_info_with_fs(args, &fs).unwrap();
+    assert_eq!(cfg.server_port, 2);
+}
+
+#[test]
+fn config_case_kebab_is_canonical_in_templates() {
+    let (cfg, _, _) = KebabConfig::parse_info_with_fs(["app"], &MemoryFs::new("/"));
+    let mut buf = Vec::new();
+    cfg.write_config_template("yaml", &mut buf).unwrap();
+    let text = String::from_utf8(buf).unwrap();
+
+    assert!(text.contains("server-port: 1"), "{}", text);
+}
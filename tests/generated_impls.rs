@@ -0,0 +1,319 @@
+use clap_config_file::ClapConfigFile;
+
+#[derive(ClapConfigFile)]
+#[config_from_str]
+struct PortConfig {
+    #[config_arg()]
+    pub port: u16,
+}
+
+#[test]
+fn from_str_parses_single_field_config() {
+    let cfg: PortConfig = "8080".parse().unwrap();
+    assert_eq!(cfg.port, 8080);
+
+    assert!("not-a-port".parse::<PortConfig>().is_err());
+}
+
+#[derive(ClapConfigFile)]
+#[config_hash_eq]
+struct CacheKeyConfig {
+    #[config_arg()]
+    pub host: String,
+
+    #[config_arg()]
+    pub port: u16,
+}
+
+#[test]
+fn hash_eq_config_works_as_map_key() {
+    let key = |host: &str, port| CacheKeyConfig {
+        host: host.to_string(),
+        port,
+    };
+    let mut cache = std::collections::HashMap::new();
+    cache.insert(key("a", 1), "first");
+    cache.insert(key("a", 2), "second");
+    cache.insert(key("a", 1), "replaced");
+
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache[&key("a", 1)], "replaced");
+    assert!(key("a", 1) != key("b", 1));
+}
+
+#[derive(ClapConfigFile)]
+struct WithInternalConfig {
+    #[config_arg()]
+    pub port: u16,
+
+    pub computed: String,
+}
+
+#[derive(ClapConfigFile)]
+#[config_serialize_internal]
+struct IncludeInternalConfig {
+    #[config_arg()]
+    pub port: u16,
+
+    pub computed: String,
+}
+
+#[test]
+fn serialize_skips_internal_fields_by_default() {
+    let cfg = WithInternalConfig {
+        port: 1,
+        computed: "secret".into(),
+    };
+    assert_eq!(
+        serde_json::to_value(&cfg).unwrap(),
+        serde_json::json!({"port": 1})
+    );
+
+    let cfg = IncludeInternalConfig {
+        port: 1,
+        computed: "x".into(),
+    };
+    assert_eq!(
+        serde_json::to_value(&cfg).unwrap(),
+        serde_json::json!({"port": 1, "computed": "x"})
+    );
+}
+
+mod private {
+    use clap_config_file::ClapConfigFile;
+
+    #[derive(ClapConfigFile)]
+    #[config_getters]
+    pub struct GetterConfig {
+        #[config_arg()]
+        port: u16,
+
+        #[config_arg()]
+        host: String,
+
+        #[config_arg()]
+        tags: Vec<String>,
+    }
+
+    pub fn build() -> GetterConfig {
+        GetterConfig {
+            port: 8080,
+            host: "localhost".into(),
+            tags: vec!["a".into()],
+        }
+    }
+}
+
+#[test]
+fn getters_expose_private_fields() {
+    let cfg = private::build();
+    let port: u16 = cfg.port();
+    let host: &str = cfg.host();
+    assert_eq!(port, 8080);
+    assert_eq!(host, "localhost");
+    assert_eq!(cfg.tags(), &vec!["a".to_string()]);
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct LockedConfig {
+    #[config_arg()]
+    pub port: u16,
+
+    #[config_arg()]
+    pub host: String,
+}
+
+fn locked_fs() -> clap_config_file::MemoryFs {
+    clap_config_file::MemoryFs::new("/project").file("app.yaml", "port: 1\nhost: h\n")
+}
+
+#[test]
+fn lock_config_then_verify_matches() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let lock = dir.path().join("app.lock");
+
+    let (cfg, _, _, details) =
+        LockedConfig::try_parse_info_with_details_fs(["app"], &locked_fs()).unwrap();
+    cfg.lock_config(&lock, details.merged_config_paths())
+        .unwrap();
+    let written = std::fs::read_to_string(&lock).unwrap();
+    assert!(written.contains("/project/app.yaml"), "{}", written);
+
+    let (cfg, _, _) = LockedConfig::parse_info_with_fs(["app"], &locked_fs());
+    cfg.verify_against_lock(&lock).unwrap();
+}
+
+#[test]
+fn verify_against_lock_detects_drift() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let lock = dir.path().join("app.lock");
+
+    let (cfg, _, _, details) =
+        LockedConfig::try_parse_info_with_details_fs(["app"], &locked_fs()).unwrap();
+    cfg.lock_config(&lock, details.merged_config_paths())
+        .unwrap();
+
+    let (cfg, _, _) = LockedConfig::parse_info_with_fs(["app", "--port", "2"], &locked_fs());
+    let err = cfg.verify_against_lock(&lock).err().unwrap();
+    assert!(err.to_string().contains("changed port"), "{}", err);
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+#[config_file_formats = "yaml"]
+struct SecretConfig {
+    #[config_arg()]
+    pub port: u16,
+
+    #[config_arg(sensitive)]
+    pub token: String,
+}
+
+#[test]
+fn lock_and_save_keep_sensitive_fields_out() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let lock = dir.path().join("app.lock");
+    let fs =
+        clap_config_file::MemoryFs::new("/project").file("app.yaml", "port: 1\ntoken: hunter2\n");
+
+    let (cfg, _, _) = SecretConfig::parse_info_with_fs(["app"], &fs);
+    cfg.lock_config(&lock, &[]).unwrap();
+    let written = std::fs::read_to_string(&lock).unwrap();
+    assert!(!written.contains("hunter2"), "{}", written);
+    cfg.verify_against_lock(&lock).unwrap();
+
+    let saved = dir.path().join("app.yaml");
+    cfg.save_to(&saved).unwrap();
+    let written = std::fs::read_to_string(&saved).unwrap();
+    assert!(!written.contains("hunter2"), "{}", written);
+    assert!(written.contains("port: 1"), "{}", written);
+
+    let (cfg, _, _) = SecretConfig::parse_info_with_fs(["app", "--token", "other"], &fs);
+    let err = cfg.verify_against_lock(&lock).err().unwrap();
+    assert!(err.to_string().contains("changed token"), "{}", err);
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+#[config_file_formats = "yaml,toml,json"]
+struct SavedConfig {
+    #[config_arg()]
+    pub port: u16,
+
+    #[config_arg(accept_from = "config_only")]
+    pub timeout: std::time::Duration,
+
+    #[config_arg(rest)]
+    pub extra: std::collections::HashMap<String, serde_json::Value>,
+}
+
+#[test]
+fn save_to_round_trips_in_each_format() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let fs = clap_config_file::MemoryFs::new("/project").file(
+        "app.yaml",
+        "port: 1
+timeout: 1500ms
+plugin: { level: 2 }
+",
+    );
+    let (cfg, _, _) = SavedConfig::parse_info_with_fs(["app", "--port", "2"], &fs);
+
+    for name in ["saved.yaml", "saved.toml", "saved.json"] {
+        let path = dir.path().join(name);
+        cfg.save_to(&path).unwrap();
+
+        let path = path.to_str().unwrap();
+        let saved = SavedConfig::parse_from(["app", "--config-file", path]);
+        assert_eq!(saved.port, 2, "{}", name);
+        assert_eq!(
+            saved.timeout,
+            std::time::Duration::from_millis(1500),
+            "{}",
+            name
+        );
+        assert_eq!(saved.extra, cfg.extra, "{}", name);
+    }
+}
+
+#[test]
+fn save_to_rejects_an_unknown_extension() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let (cfg, _, _) =
+        SavedConfig::parse_info_with_fs(["app"], &clap_config_file::MemoryFs::new("/"));
+
+    let err = cfg.save_to(dir.path().join("saved.ini")).err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(!dir.path().join("saved.ini").exists());
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct AuditedConfig {
+    #[config_arg()]
+    pub port: u16,
+
+    #[config_arg()]
+    pub host: String,
+
+    #[config_arg(sensitive)]
+    pub token: String,
+}
+
+#[test]
+fn diff_reports_changed_fields_only() {
+    let fs = clap_config_file::MemoryFs::new("/project")
+        .file("app.yaml", "port: 1\nhost: h\ntoken: t1\n");
+    let (a, _, _) = AuditedConfig::parse_info_with_fs(["app"], &fs);
+    let (b, _, _) = AuditedConfig::parse_info_with_fs(["app", "--port", "2", "--token", "t2"], &fs);
+
+    let diffs = AuditedConfig::diff(&a, &b);
+    assert_eq!(
+        diffs.iter().map(|d| d.field).collect::<Vec<_>>(),
+        ["port", "token"]
+    );
+    assert_eq!(diffs[0].old, 1);
+    assert_eq!(diffs[0].new, 2);
+    assert_eq!(diffs[0].to_string(), "port: 1 -> 2");
+    assert!(!diffs[1].to_string().contains("t2"), "{}", diffs[1]);
+
+    assert!(AuditedConfig::diff(&a, &a).is_empty());
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct SchemaConfig {
+    /// Port to listen on
+    #[config_arg(default_value = "8080")]
+    pub port: u16,
+
+    #[config_arg(accept_from = "config_only", default_value = "a,b")]
+    pub tags: Vec<String>,
+
+    #[config_arg()]
+    pub region: Option<String>,
+
+    #[config_arg(accept_from = "cli_only")]
+    pub verbose: bool,
+}
+
+#[test]
+fn config_json_schema_describes_config_keys() {
+    let schema = SchemaConfig::config_json_schema();
+    assert_eq!(schema["type"], "object");
+    assert_eq!(schema["title"], "SchemaConfig");
+
+    let props = &schema["properties"];
+    assert_eq!(props["port"]["type"], "integer");
+    assert_eq!(props["port"]["default"], 8080);
+    assert_eq!(props["port"]["description"], "Port to listen on");
+    assert_eq!(props["tags"]["type"], "array");
+    assert_eq!(props["tags"]["items"]["type"], "string");
+    assert_eq!(props["tags"]["default"], serde_json::json!(["a", "b"]));
+    assert_eq!(
+        props["region"]["type"],
+        serde_json::json!(["string", "null"])
+    );
+    assert!(props.get("verbose").is_none());
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "http")]
+
+use clap_config_file::{ClapConfigError, ClapConfigFile, MemoryFs};
+use std::io::{Read, Write};
+use std::net::TcpListener;
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+#[config_file_formats = "yaml,toml"]
+struct RemoteConfig {
+    #[config_arg()]
+    pub port: u16,
+}
+
+/// Serve one HTTP response with `status` and `body`; returns the base URL.
+fn serve_once(status: &'static str, body: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut buf = [0u8; 1024];
+        let _ = stream.read(&mut buf);
+        let _ = write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+    });
+    format!("http://{}", addr)
+}
+
+#[test]
+fn config_file_url_is_fetched() {
+    let url = format!(
+        "{}/app.toml?ref=main",
+        serve_once("200 OK", "port = 9000\n")
+    );
+
+    let (cfg, path, fmt) = RemoteConfig::parse_info_with_fs(
+        ["app", "--config-file", url.as_str()],
+        &MemoryFs::new("/"),
+    );
+
+    assert_eq!(cfg.port, 9000);
+    assert!(path.is_none());
+    assert_eq!(fmt, Some("toml"));
+}
+
+#[test]
+fn config_file_url_error_status_fails() {
+    let url = format!("{}/app.yaml", serve_once("404 Not Found", ""));
+
+    let err = RemoteConfig::try_parse_info_with_fs(
+        ["app", "--config-file", url.as_str()],
+        &MemoryFs::new("/"),
+    )
+    .err()
+    .unwrap();
+    assert!(
+        matches!(err, ClapConfigError::ConfigReadError { .. }),
+        "{}",
+        err
+    );
+}
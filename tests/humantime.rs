@@ -0,0 +1,26 @@
+#![cfg(feature = "humantime")]
+
+use clap_config_file::{ClapConfigFile, MemoryFs};
+use std::time::Duration;
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct HumantimeConfig {
+    #[config_arg(parse_with = "humantime")]
+    pub timeout: Duration,
+
+    #[config_arg(parse_with = "humantime", default_value = "1day")]
+    pub retention: Duration,
+}
+
+#[test]
+fn humantime_durations_from_cli_config_and_default() {
+    let fs = MemoryFs::new("/project").file("app.yaml", "timeout: 2h 30min\n");
+
+    let (cfg, _, _) = HumantimeConfig::parse_info_with_fs(["app"], &fs);
+    assert_eq!(cfg.timeout, Duration::from_secs(150 * 60));
+    assert_eq!(cfg.retention, Duration::from_secs(24 * 60 * 60));
+
+    let (cfg, _, _) = HumantimeConfig::parse_info_with_fs(["app", "--timeout", "15 seconds"], &fs);
+    assert_eq!(cfg.timeout, Duration::from_secs(15));
+}
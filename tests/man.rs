@@ -0,0 +1,33 @@
+use clap_config_file::ClapConfigFile;
+
+/// Serve files over HTTP.
+///
+/// Reads app.yaml from the current directory or any parent.
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct ServeConfig {
+    /// Port to listen on
+    #[config_arg(short = 'p')]
+    pub port: u16,
+}
+
+#[cfg(feature = "man")]
+#[test]
+fn man_page_has_about_and_flags() {
+    let mut buf = Vec::new();
+    ServeConfig::generate_man_page(&mut buf).unwrap();
+    let page = String::from_utf8(buf).unwrap();
+
+    assert!(page.contains(".TH"), "{}", page);
+    assert!(page.contains("Serve files over HTTP."), "{}", page);
+    assert!(page.contains("Reads app.yaml"), "{}", page);
+    assert!(page.contains("Port to listen on"), "{}", page);
+    assert!(page.contains("config\\-file"), "{}", page);
+}
+
+#[cfg(not(feature = "man"))]
+#[test]
+fn man_page_needs_man_feature() {
+    let err = ServeConfig::generate_man_page(&mut Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+}
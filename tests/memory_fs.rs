@@ -0,0 +1,582 @@
+use clap_config_file::{ClapConfigError, ClapConfigFile, MemoryFs};
+use std::path::PathBuf;
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+#[config_file_formats = "yaml,json"]
+struct AppConfig {
+    #[config_arg()]
+    pub port: u16,
+
+    #[config_arg()]
+    pub database_url: String,
+
+    #[config_arg(multi_value_behavior = "extend")]
+    pub tags: Vec<String>,
+}
+
+#[test]
+fn discovers_config_in_parent_dir() {
+    let fs = MemoryFs::new("/project/sub/dir").file(
+        "/project/app.yaml",
+        "port: 8080\ndatabase_url: sqlite://mem.db\n",
+    );
+
+    let (cfg, path, fmt) = AppConfig::parse_info_with_fs(["app"], &fs);
+
+    assert_eq!(cfg.port, 8080);
+    assert_eq!(cfg.database_url, "sqlite://mem.db");
+    assert_eq!(path, Some(PathBuf::from("/project/app.yaml")));
+    assert_eq!(fmt, Some("yaml"));
+}
+
+#[test]
+fn cli_overrides_and_extends_in_memory_config() {
+    let fs = MemoryFs::new("/project").file("app.json", r#"{"port": 1, "tags": ["a"]}"#);
+
+    let (cfg, _, fmt) = AppConfig::parse_info_with_fs(["app", "--port", "2", "--tags", "b"], &fs);
+
+    assert_eq!(cfg.port, 2);
+    assert_eq!(cfg.tags, vec!["a", "b"]);
+    assert_eq!(fmt, Some("json"));
+}
+
+#[test]
+fn explicit_config_file_is_read_from_memory() {
+    let fs = MemoryFs::new("/project")
+        .file("app.yaml", "port: 1\n")
+        .file("/elsewhere/custom.yaml", "port: 3\n");
+
+    let (cfg, path, _) =
+        AppConfig::parse_info_with_fs(["app", "--config-file", "/elsewhere/custom.yaml"], &fs);
+
+    assert_eq!(cfg.port, 3);
+    assert_eq!(path, Some(PathBuf::from("/elsewhere/custom.yaml")));
+}
+
+#[test]
+fn empty_fs_disables_discovery() {
+    let (cfg, path, fmt) = AppConfig::parse_info_with_fs(["app"], &MemoryFs::new("/"));
+
+    assert_eq!(cfg.port, 0);
+    assert!(path.is_none());
+    assert!(fmt.is_none());
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+#[config_file_formats = "toml,yaml"]
+#[config_file_format_precedence]
+struct PrecedenceConfig {
+    #[config_arg()]
+    pub port: u16,
+}
+
+#[test]
+fn declared_format_order_breaks_same_dir_ties() {
+    let fs = MemoryFs::new("/project")
+        .file("app.yaml", "port: 1\n")
+        .file("app.toml", "port = 2\n");
+
+    let (cfg, path, fmt) = PrecedenceConfig::parse_info_with_fs(["app"], &fs);
+
+    assert_eq!(cfg.port, 2);
+    assert_eq!(path, Some(PathBuf::from("/project/app.toml")));
+    assert_eq!(fmt, Some("toml"));
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_cargo_metadata = "mytool"]
+struct CargoToolConfig {
+    #[config_arg()]
+    pub port: u16,
+
+    #[config_arg()]
+    pub name: String,
+}
+
+#[test]
+fn reads_package_metadata_table_from_nearest_cargo_toml() {
+    let fs = MemoryFs::new("/ws/crates/tool/src").file(
+        "/ws/crates/tool/Cargo.toml",
+        "[package]\nname = \"tool\"\n\n[package.metadata.mytool]\nport = 8080\nname = \"from-cargo\"\n",
+    );
+
+    let (cfg, path, fmt) = CargoToolConfig::parse_info_with_fs(["tool"], &fs);
+
+    assert_eq!(cfg.port, 8080);
+    assert_eq!(cfg.name, "from-cargo");
+    assert_eq!(path, Some(PathBuf::from("/ws/crates/tool/Cargo.toml")));
+    assert_eq!(fmt, Some("toml"));
+}
+
+#[test]
+fn package_metadata_wins_over_workspace_metadata() {
+    let fs = MemoryFs::new("/ws").file(
+        "Cargo.toml",
+        "[workspace.metadata.mytool]\nport = 1\nname = \"ws\"\n\n[package.metadata.mytool]\nport = 2\n",
+    );
+
+    let (cfg, _, _) = CargoToolConfig::parse_info_with_fs(["tool"], &fs);
+
+    assert_eq!(cfg.port, 2);
+    assert_eq!(cfg.name, "ws");
+}
+
+#[test]
+fn malformed_metadata_table_is_an_error() {
+    let fs = MemoryFs::new("/ws").file(
+        "Cargo.toml",
+        "[package]\nname = \"tool\"\n\n[package.metadata]\nmytool = 5\n",
+    );
+
+    let err = CargoToolConfig::try_parse_info_with_fs(["tool"], &fs)
+        .err()
+        .unwrap();
+    assert!(
+        matches!(err, ClapConfigError::ConfigDeserializeError(_)),
+        "{}",
+        err
+    );
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+#[config_file_cli_file_mode = "layer"]
+struct LayerModeConfig {
+    #[config_arg()]
+    pub port: u16,
+
+    #[config_arg()]
+    pub name: String,
+}
+
+#[test]
+fn layer_mode_keeps_discovered_values() {
+    let fs = MemoryFs::new("/project")
+        .file("app.yaml", "port: 1\nname: discovered\n")
+        .file("/tmp/override.yaml", "port: 2\n");
+
+    let (cfg, path, _) =
+        LayerModeConfig::parse_info_with_fs(["app", "--config-file", "/tmp/override.yaml"], &fs);
+
+    assert_eq!(cfg.port, 2);
+    assert_eq!(cfg.name, "discovered");
+    assert_eq!(path, Some(PathBuf::from("/tmp/override.yaml")));
+}
+
+#[test]
+fn replace_mode_ignores_discovered_values() {
+    let fs = MemoryFs::new("/project")
+        .file("app.yaml", "port: 1\ndatabase_url: discovered\n")
+        .file("/tmp/override.yaml", "port: 2\n");
+
+    let (cfg, _, _) =
+        AppConfig::parse_info_with_fs(["app", "--config-file", "/tmp/override.yaml"], &fs);
+
+    assert_eq!(cfg.port, 2);
+    assert_eq!(cfg.database_url, "");
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_recursive = "config"]
+#[config_file_formats = "yaml,toml"]
+struct RecursiveConfig {
+    #[config_arg()]
+    pub port: u16,
+
+    #[config_arg()]
+    pub name: String,
+
+    #[config_arg()]
+    pub region: String,
+}
+
+#[test]
+fn recursive_discovery_merges_subtree_in_path_order() {
+    let fs = MemoryFs::new("/project")
+        .file("config/00-base.yaml", "port: 1\nname: base\n")
+        .file("config/prod/region.toml", "region = \"eu\"\n")
+        .file("config/prod/zz/port.yaml", "port: 3\n")
+        .file("config/README.md", "not config")
+        .file("other/app.yaml", "name: outside\n");
+
+    let (cfg, path, _) = RecursiveConfig::parse_info_with_fs(["app"], &fs);
+
+    assert_eq!(cfg.port, 3);
+    assert_eq!(cfg.name, "base");
+    assert_eq!(cfg.region, "eu");
+    assert_eq!(
+        path,
+        Some(PathBuf::from("/project/config/prod/zz/port.yaml"))
+    );
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "/etc/myapp/config.toml"]
+#[config_file_formats = "yaml,toml"]
+struct FixedPathConfig {
+    #[config_arg()]
+    pub port: u16,
+}
+
+#[test]
+fn absolute_config_file_name_skips_discovery() {
+    let fs = MemoryFs::new("/project")
+        .file("/etc/myapp/config.toml", "port = 7\n")
+        .file("/project/config.toml", "port = 1\n");
+
+    let (cfg, path, fmt) = FixedPathConfig::parse_info_with_fs(["app"], &fs);
+
+    assert_eq!(cfg.port, 7);
+    assert_eq!(path, Some(PathBuf::from("/etc/myapp/config.toml")));
+    assert_eq!(fmt, Some("toml"));
+}
+
+#[test]
+fn missing_absolute_config_file_means_no_config() {
+    let fs = MemoryFs::new("/etc/myapp").file("config.yaml", "port: 1\n");
+
+    let (cfg, path, _) = FixedPathConfig::parse_info_with_fs(["app"], &fs);
+
+    assert_eq!(cfg.port, 0);
+    assert!(path.is_none());
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+#[config_file_require_explicit]
+struct ExplicitOnlyConfig {
+    #[config_arg()]
+    pub port: u16,
+}
+
+#[test]
+fn require_explicit_rejects_discovery() {
+    let fs = MemoryFs::new("/project").file("app.yaml", "port: 1\n");
+
+    let err = ExplicitOnlyConfig::try_parse_info_with_fs(["app"], &fs)
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("--config-file"), "{}", err);
+}
+
+#[test]
+fn require_explicit_accepts_explicit_sources() {
+    let fs = MemoryFs::new("/project")
+        .file("app.yaml", "port: 1\n")
+        .file("ci.yaml", "port: 2\n");
+
+    let (cfg, _, _) =
+        ExplicitOnlyConfig::parse_info_with_fs(["app", "--config-file", "ci.yaml"], &fs);
+    assert_eq!(cfg.port, 2);
+
+    // --config alone doesn't pull in the discoverable app.yaml
+    let (cfg, path, _) =
+        ExplicitOnlyConfig::parse_info_with_fs(["app", "--config", "port: 3"], &fs);
+    assert_eq!(cfg.port, 3);
+    assert!(path.is_none());
+
+    let (cfg, _, _) = ExplicitOnlyConfig::parse_info_with_fs(["app", "--no-config"], &fs);
+    assert_eq!(cfg.port, 0);
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+#[config_file_merge = "layered"]
+struct LayeredConfig {
+    #[config_arg()]
+    pub port: u16,
+
+    #[config_arg()]
+    pub name: String,
+
+    #[config_arg()]
+    pub region: String,
+}
+
+#[test]
+fn layered_merge_lets_nearest_file_win_per_key() {
+    let fs = MemoryFs::new("/repo/services/api")
+        .file("/repo/app.yaml", "port: 1\nname: root\nregion: eu\n")
+        .file("/repo/services/app.yaml", "port: 2\nname: services\n")
+        .file("/repo/services/api/app.yaml", "port: 3\n");
+
+    let (cfg, path, _, details) =
+        LayeredConfig::try_parse_info_with_details_fs(["app"], &fs).unwrap();
+
+    assert_eq!(cfg.port, 3);
+    assert_eq!(cfg.name, "services");
+    assert_eq!(cfg.region, "eu");
+    assert_eq!(path, Some(PathBuf::from("/repo/services/api/app.yaml")));
+    assert_eq!(
+        details.merged_config_paths(),
+        [
+            PathBuf::from("/repo/app.yaml"),
+            PathBuf::from("/repo/services/app.yaml"),
+            PathBuf::from("/repo/services/api/app.yaml"),
+        ]
+    );
+}
+
+#[test]
+fn strict_merge_rejects_files_at_several_levels() {
+    let fs = MemoryFs::new("/repo/sub")
+        .file("/repo/app.yaml", "port: 1\n")
+        .file("/repo/sub/app.yaml", "port: 2\n");
+
+    assert!(AppConfig::try_parse_info_with_fs(["app"], &fs).is_err());
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct ResetConfig {
+    #[config_arg()]
+    pub port: u16,
+
+    #[config_arg(exclusive_with_config, accept_from = "cli_only")]
+    pub reset: bool,
+}
+
+#[test]
+fn exclusive_with_config_flag_acts_like_no_config() {
+    let fs = MemoryFs::new("/project").file("app.yaml", "port: 1\n");
+
+    let (cfg, path, _) = ResetConfig::parse_info_with_fs(["app"], &fs);
+    assert_eq!(cfg.port, 1);
+    assert!(path.is_some());
+
+    let (cfg, path, _) = ResetConfig::parse_info_with_fs(["app", "--reset"], &fs);
+    assert_eq!(cfg.port, 0);
+    assert!(cfg.reset);
+    assert!(path.is_none());
+
+    let (cfg, _, _) = ResetConfig::parse_info_with_fs(["app", "--reset=false"], &fs);
+    assert_eq!(cfg.port, 1);
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+#[config_file_search = "cwd,xdg,home"]
+struct UserLevelConfig {
+    #[config_arg()]
+    pub port: u16,
+}
+
+#[test]
+fn search_falls_back_to_user_level_locations() {
+    let home = MemoryFs::new("/project")
+        .home("/home/u")
+        .file("/home/u/.app.yaml", "port: 3\n");
+    let (cfg, path, _) = UserLevelConfig::parse_info_with_fs(["app"], &home);
+    assert_eq!(cfg.port, 3);
+    assert_eq!(path, Some(PathBuf::from("/home/u/.app.yaml")));
+
+    // ~/.config is the XDG default; it beats ~/.app.yaml
+    let dot_config = home
+        .clone()
+        .file("/home/u/.config/app/app.yaml", "port: 2\n");
+    let (cfg, _, _) = UserLevelConfig::parse_info_with_fs(["app"], &dot_config);
+    assert_eq!(cfg.port, 2);
+
+    // $XDG_CONFIG_HOME replaces ~/.config
+    let xdg = dot_config
+        .clone()
+        .xdg_config_home("/xdg")
+        .file("/xdg/app/app.yaml", "port: 4\n");
+    let (cfg, path, _) = UserLevelConfig::parse_info_with_fs(["app"], &xdg);
+    assert_eq!(cfg.port, 4);
+    assert_eq!(path, Some(PathBuf::from("/xdg/app/app.yaml")));
+
+    // Project-local beats all user-level files
+    let local = xdg.file("/project/app.yaml", "port: 1\n");
+    let (cfg, path, _) = UserLevelConfig::parse_info_with_fs(["app"], &local);
+    assert_eq!(cfg.port, 1);
+    assert_eq!(path, Some(PathBuf::from("/project/app.yaml")));
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+#[config_file_search = "home,cwd"]
+struct HomeFirstConfig {
+    #[config_arg()]
+    pub port: u16,
+}
+
+#[test]
+fn search_order_is_configurable() {
+    let fs = MemoryFs::new("/project")
+        .home("/home/u")
+        .file("/home/u/.app.yaml", "port: 3\n")
+        .file("/project/app.yaml", "port: 1\n");
+    let (cfg, _, _) = HomeFirstConfig::parse_info_with_fs(["app"], &fs);
+    assert_eq!(cfg.port, 3);
+}
+
+#[test]
+fn config_file_path_expands_env_vars_and_tilde() {
+    std::env::set_var("CCF_TEST_DEPLOY_ENV", "staging");
+    let fs = MemoryFs::new("/project")
+        .home("/home/u")
+        .file("/configs/staging/app.yaml", "port: 7\n")
+        .file("/home/u/app.yaml", "port: 8\n");
+
+    let (cfg, path, _) = UserLevelConfig::parse_info_with_fs(
+        [
+            "app",
+            "--config-file",
+            "/configs/${CCF_TEST_DEPLOY_ENV}/app.yaml",
+        ],
+        &fs,
+    );
+    assert_eq!(cfg.port, 7);
+    assert_eq!(path, Some(PathBuf::from("/configs/staging/app.yaml")));
+
+    let (cfg, _, _) =
+        UserLevelConfig::parse_info_with_fs(["app", "--config-file", "~/app.yaml"], &fs);
+    assert_eq!(cfg.port, 8);
+
+    let err = UserLevelConfig::try_parse_info_with_fs(
+        [
+            "app",
+            "--config-file",
+            "/configs/$CCF_TEST_UNSET_ENV/app.yaml",
+        ],
+        &fs,
+    )
+    .err()
+    .unwrap();
+    assert!(
+        err.to_string().contains("CCF_TEST_UNSET_ENV is not set"),
+        "{}",
+        err
+    );
+}
+
+#[cfg(windows)]
+#[test]
+fn discovers_config_above_windows_cwd() {
+    let fs = MemoryFs::new(r"C:\project\sub\dir").file(r"C:\project\app.json", r#"{"port": 8080}"#);
+
+    let (cfg, path, fmt) = AppConfig::parse_info_with_fs(["app"], &fs);
+
+    assert_eq!(cfg.port, 8080);
+    assert_eq!(path, Some(PathBuf::from(r"C:\project\app.json")));
+    assert_eq!(fmt, Some("json"));
+}
+
+#[cfg(windows)]
+#[test]
+fn windows_config_file_paths_keep_drive_letters() {
+    let fs = MemoryFs::new(r"C:\project")
+        .home(r"C:\Users\u")
+        .file(r"D:\configs\settings.conf", r#"{"port": 2}"#)
+        .file(r"C:\Users\u\app.yaml", "port: 3\n");
+
+    let (cfg, path, fmt) = AppConfig::parse_info_with_fs(
+        ["app", "--config-file", r"D:\configs\settings.conf:json"],
+        &fs,
+    );
+    assert_eq!(cfg.port, 2);
+    assert_eq!(path, Some(PathBuf::from(r"D:\configs\settings.conf")));
+    assert_eq!(fmt, Some("json"));
+
+    let (cfg, _, _) = AppConfig::parse_info_with_fs(["app", "--config-file", r"~\app.yaml"], &fs);
+    assert_eq!(cfg.port, 3);
+}
+
+#[cfg(windows)]
+#[test]
+fn real_fs_user_config_dir_is_appdata_on_windows() {
+    use clap_config_file::{ConfigFs, RealFs};
+
+    if std::env::var_os("XDG_CONFIG_HOME").is_none() {
+        assert_eq!(
+            RealFs.xdg_config_home(),
+            std::env::var_os("APPDATA").map(PathBuf::from)
+        );
+    }
+}
+
+#[test]
+fn config_file_accepts_comma_separated_paths() {
+    let fs = MemoryFs::new("/project")
+        .file("base.yaml", "port: 1\ndatabase_url: base\n")
+        .file("ci.yaml", "port: 2\n")
+        .file("odd,name.yaml", "database_url: odd\n");
+
+    let (cfg, path, _) =
+        AppConfig::parse_info_with_fs(["app", "--config-file", "base.yaml,ci.yaml"], &fs);
+    assert_eq!(cfg.port, 2);
+    assert_eq!(cfg.database_url, "base");
+    assert_eq!(path, Some(PathBuf::from("ci.yaml")));
+
+    let (cfg, _, _) =
+        AppConfig::parse_info_with_fs(["app", "--config-file", r"ci.yaml,odd\,name.yaml"], &fs);
+    assert_eq!(cfg.port, 2);
+    assert_eq!(cfg.database_url, "odd");
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+#[config_file_formats = "yaml,json"]
+#[config_file_include]
+struct IncludingConfig {
+    #[config_arg()]
+    pub port: u16,
+
+    #[config_arg()]
+    pub database_url: String,
+
+    #[config_arg(multi_value_behavior = "extend")]
+    pub tags: Vec<String>,
+}
+
+#[test]
+fn included_files_sit_below_the_including_one() {
+    let fs = MemoryFs::new("/project")
+        .file(
+            "/project/app.yaml",
+            "include: [shared/base.yaml, shared/db.json]\nport: 3\n",
+        )
+        .file(
+            "/project/shared/base.yaml",
+            "include: ../defaults.yaml\nport: 1\ndatabase_url: base\n",
+        )
+        .file("/project/shared/db.json", r#"{"database_url": "db"}"#)
+        .file("/project/defaults.yaml", "port: 0\ntags: [default]\n");
+
+    let (cfg, path, _, details) =
+        IncludingConfig::try_parse_info_with_details_fs(["app"], &fs).unwrap();
+
+    assert_eq!(cfg.port, 3);
+    assert_eq!(cfg.database_url, "db");
+    assert_eq!(cfg.tags, vec!["default"]);
+    assert_eq!(path, Some(PathBuf::from("/project/app.yaml")));
+    assert_eq!(
+        details.merged_config_paths(),
+        [
+            "/project/defaults.yaml",
+            "/project/shared/base.yaml",
+            "/project/shared/db.json",
+            "/project/app.yaml",
+        ]
+        .map(PathBuf::from)
+    );
+}
+
+#[test]
+fn cyclic_include_is_an_error() {
+    let fs = MemoryFs::new("/project")
+        .file("/project/app.yaml", "include: a.yaml\n")
+        .file("/project/a.yaml", "include: ./b.yaml\n")
+        .file("/project/b.yaml", "include: a.yaml\nport: 1\n");
+
+    let err = IncludingConfig::try_parse_info_with_fs(["app"], &fs)
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.to_string(),
+        "cyclic config include: /project/a.yaml -> /project/b.yaml -> /project/a.yaml"
+    );
+}
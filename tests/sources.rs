@@ -0,0 +1,95 @@
+use clap_config_file::{ClapConfigFile, MemoryFs};
+use config::{ConfigError, Map, Source, Value};
+
+/// Stand-in for a remote store like Consul or Vault.
+#[derive(Debug, Clone)]
+struct KvStore(Vec<(&'static str, &'static str)>);
+
+impl Source for KvStore {
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+    fn collect(&self) -> Result<Map<String, Value>, ConfigError> {
+        Ok(self
+            .0
+            .iter()
+            .map(|(k, v)| (k.to_string(), Value::new(Some(&"kv".to_string()), *v)))
+            .collect())
+    }
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct AppConfig {
+    #[config_arg()]
+    pub port: u16,
+
+    #[config_arg()]
+    pub name: String,
+
+    #[config_arg()]
+    pub region: String,
+}
+
+#[test]
+fn custom_source_layers_over_files_and_under_cli() {
+    let fs = MemoryFs::new("/project").file("app.yaml", "port: 1\nname: file\nregion: us\n");
+    let kv = KvStore(vec![("port", "2"), ("name", "kv")]);
+
+    let (cfg, _, _) = AppConfig::parse_info_with_fs_and_sources(
+        ["app", "--name", "cli"],
+        &fs,
+        vec![Box::new(kv)],
+    );
+
+    assert_eq!(cfg.port, 2);
+    assert_eq!(cfg.name, "cli");
+    assert_eq!(cfg.region, "us");
+}
+
+#[test]
+fn later_custom_sources_win() {
+    let (cfg, _, _) = AppConfig::parse_info_with_fs_and_sources(
+        ["app"],
+        &MemoryFs::new("/"),
+        vec![
+            Box::new(KvStore(vec![("port", "1"), ("region", "eu")])),
+            Box::new(KvStore(vec![("port", "2")])),
+        ],
+    );
+
+    assert_eq!(cfg.port, 2);
+    assert_eq!(cfg.region, "eu");
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "ccf-test-parse-info-from"]
+#[config_file_formats = "yaml,toml"]
+struct OverrideConfig {
+    #[config_arg()]
+    pub port: u16,
+
+    #[config_arg()]
+    pub name: String,
+}
+
+#[test]
+fn parse_info_from_takes_args_and_config_string() {
+    let (cfg, _, _) = OverrideConfig::parse_info_from(
+        ["app", "--name", "cli"],
+        Some("port = 7\nname = \"toml\"\n"),
+    );
+    assert_eq!(cfg.port, 7);
+    assert_eq!(cfg.name, "cli");
+
+    let (cfg, _, _) = OverrideConfig::parse_info_from(["app"], None);
+    assert_eq!(cfg.port, 0);
+}
+
+#[test]
+fn try_parse_info_from_returns_a_bad_override() {
+    let err = OverrideConfig::try_parse_info_from(["app"], Some("[not toml"))
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "config_override is not valid yaml or toml");
+}
@@ -0,0 +1,444 @@
+use clap_config_file::{ClapConfigFile, MemoryFs};
+use std::time::Duration;
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct DurationConfig {
+    #[config_arg()]
+    pub timeout: Duration,
+
+    #[config_arg(default_value = "5m")]
+    pub interval: std::time::Duration,
+}
+
+#[test]
+fn duration_from_cli() {
+    let (cfg, _, _) =
+        DurationConfig::parse_info_with_fs(["app", "--timeout", "1h30m"], &MemoryFs::new("/"));
+
+    assert_eq!(cfg.timeout, Duration::from_secs(90 * 60));
+    assert_eq!(cfg.interval, Duration::from_secs(300));
+}
+
+#[test]
+fn duration_from_config() {
+    let fs = MemoryFs::new("/project").file("app.yaml", "timeout: \"90s\"\n");
+
+    let (cfg, _, _) = DurationConfig::parse_info_with_fs(["app"], &fs);
+
+    assert_eq!(cfg.timeout, Duration::from_secs(90));
+}
+
+#[test]
+fn duration_cli_overrides_config() {
+    let fs = MemoryFs::new("/project").file("app.yaml", "timeout: 30\n");
+
+    let (cfg, _, _) = DurationConfig::parse_info_with_fs(["app", "--timeout", "500ms"], &fs);
+
+    assert_eq!(cfg.timeout, Duration::from_millis(500));
+}
+
+#[test]
+fn duration_out_of_range_is_rejected() {
+    let err = DurationConfig::try_parse_info_with_fs(
+        ["app", "--timeout", "999999999999999999d"],
+        &MemoryFs::new("/"),
+    )
+    .err()
+    .unwrap();
+    assert!(err.to_string().contains("duration out of range"), "{}", err);
+
+    let fs = MemoryFs::new("/project").file(
+        "app.yaml",
+        "timeout: 18446744073709551615s 1s
+",
+    );
+    let err = DurationConfig::try_parse_info_with_fs(["app"], &fs)
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("duration out of range"), "{}", err);
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct DurationShapesConfig {
+    #[config_arg()]
+    pub timeout: Option<Duration>,
+
+    #[config_arg(env = "CCF_TEST_TYPES_BACKOFF")]
+    pub backoff: Vec<Duration>,
+}
+
+#[test]
+fn optional_and_list_durations() {
+    let fs = MemoryFs::new("/project").file(
+        "app.yaml",
+        "backoff: [1s, 2m]
+",
+    );
+
+    let (cfg, _, _) = DurationShapesConfig::parse_info_with_fs(["app"], &fs);
+    assert_eq!(cfg.timeout, None);
+    assert_eq!(
+        cfg.backoff,
+        [Duration::from_secs(1), Duration::from_secs(120)]
+    );
+
+    let (cfg, _, _) = DurationShapesConfig::parse_info_with_fs(
+        [
+            "app",
+            "--timeout",
+            "1h",
+            "--backoff",
+            "5s",
+            "--backoff",
+            "250ms",
+        ],
+        &fs,
+    );
+    assert_eq!(cfg.timeout, Some(Duration::from_secs(3600)));
+    assert_eq!(
+        cfg.backoff[2..],
+        [Duration::from_secs(5), Duration::from_millis(250)]
+    );
+    assert_eq!(cfg.to_args()[..2], ["--timeout", "3600000ms"]);
+    assert_eq!(cfg.to_args()[8..], ["--backoff", "250ms"]);
+
+    let fs = MemoryFs::new("/project").file(
+        "app.yaml",
+        "timeout: 90
+",
+    );
+    std::env::set_var("CCF_TEST_TYPES_BACKOFF", "1s,2s");
+    let parsed = DurationShapesConfig::try_parse_info_with_fs(["app"], &fs);
+    std::env::remove_var("CCF_TEST_TYPES_BACKOFF");
+    let (cfg, _, _) = parsed.unwrap();
+    assert_eq!(cfg.timeout, Some(Duration::from_secs(90)));
+    assert_eq!(
+        cfg.backoff,
+        [Duration::from_secs(1), Duration::from_secs(2)]
+    );
+}
+
+/// `--port` on the CLI, `port:` in the config file
+#[derive(ClapConfigFile)]
+#[config_file_name = "port"]
+struct Port(u16);
+
+#[test]
+fn newtype_reads_flag_and_config_key() {
+    let fs = MemoryFs::new("/project").file("port.yaml", "port: 8080\n");
+
+    let (cfg, _, _) = Port::parse_info_with_fs(["app"], &fs);
+    assert_eq!(cfg.0, 8080);
+
+    let (cfg, _, _) = Port::parse_info_with_fs(["app", "--port", "9000"], &fs);
+    assert_eq!(cfg.0, 9000);
+}
+
+#[derive(ClapConfigFile)]
+struct Target(#[config_arg(positional)] String);
+
+#[test]
+fn newtype_can_be_positional() {
+    let (cfg, _, _) = Target::parse_info_with_fs(["app", "prod"], &MemoryFs::new("/"));
+    assert_eq!(cfg.0, "prod");
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct ScalarVecConfig {
+    #[config_arg()]
+    pub ports: Vec<u16>,
+
+    #[config_arg(multi_value_behavior = "overwrite")]
+    pub flags: Vec<bool>,
+
+    #[config_arg(positional)]
+    pub ids: Vec<u32>,
+}
+
+#[test]
+fn vec_of_u16_from_config_and_cli() {
+    let fs = MemoryFs::new("/project").file("app.yaml", "ports: [80, 443]\nflags: [true]\n");
+
+    let (cfg, _, _) = ScalarVecConfig::parse_info_with_fs(["app"], &fs);
+    assert_eq!(cfg.ports, vec![80, 443]);
+    assert_eq!(cfg.flags, vec![true]);
+
+    let (cfg, _, _) = ScalarVecConfig::parse_info_with_fs(
+        [
+            "app", "--ports", "8080", "--flags", "false", "true", "--", "7", "9",
+        ],
+        &fs,
+    );
+    assert_eq!(cfg.ports, vec![80, 443, 8080]);
+    assert_eq!(cfg.flags, vec![false, true]);
+    assert_eq!(cfg.ids, vec![7, 9]);
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct UniqueVecConfig {
+    #[config_arg(multi_value_behavior = "extend-unique")]
+    pub include: Vec<String>,
+}
+
+#[test]
+fn extend_unique_drops_repeats_keeping_order() {
+    let fs = MemoryFs::new("/project").file(
+        "app.yaml",
+        "include: [src, lib, src]
+",
+    );
+
+    let (cfg, _, _) = UniqueVecConfig::parse_info_with_fs(
+        ["app", "--include", "vendor", "lib", "--include", "vendor"],
+        &fs,
+    );
+    assert_eq!(cfg.include, ["src", "lib", "vendor"]);
+}
+
+#[test]
+fn vec_element_parse_errors_are_reported() {
+    let err =
+        ScalarVecConfig::try_parse_info_with_fs(["app", "--ports", "http"], &MemoryFs::new("/"))
+            .err()
+            .unwrap();
+    assert!(matches!(err, clap_config_file::ClapConfigError::Cli(_)));
+}
+
+#[derive(ClapConfigFile)]
+struct OptionalPositionalConfig {
+    #[config_arg(positional)]
+    pub count: Option<u8>,
+}
+
+#[test]
+fn optional_typed_positional() {
+    let (cfg, _, _) =
+        OptionalPositionalConfig::parse_info_with_fs(["app", "3"], &MemoryFs::new("/"));
+    assert_eq!(cfg.count, Some(3));
+
+    let (cfg, _, _) = OptionalPositionalConfig::parse_info_with_fs(["app"], &MemoryFs::new("/"));
+    assert_eq!(cfg.count, None);
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct OptionalVecConfig {
+    #[config_arg(env = "CCF_TEST_OPT_VEC_HOSTS")]
+    pub hosts: Option<Vec<String>>,
+
+    #[config_arg(multi_value_behavior = "overwrite")]
+    pub ports: Option<Vec<u16>>,
+
+    #[config_arg(positional)]
+    pub files: Option<Vec<String>>,
+}
+
+#[test]
+fn optional_vec_distinguishes_unset_from_empty() {
+    let (cfg, _, _) = OptionalVecConfig::parse_info_with_fs(["app"], &MemoryFs::new("/"));
+    assert_eq!(cfg.hosts, None);
+    assert_eq!(cfg.ports, None);
+    assert_eq!(cfg.files, None);
+
+    let fs = MemoryFs::new("/project").file(
+        "app.yaml",
+        "hosts: []
+ports: [80, 443]
+",
+    );
+    let (cfg, _, _) = OptionalVecConfig::parse_info_with_fs(["app"], &fs);
+    assert_eq!(cfg.hosts, Some(vec![]));
+    assert_eq!(cfg.ports, Some(vec![80, 443]));
+}
+
+#[test]
+fn optional_vec_merges_cli_with_config() {
+    let fs = MemoryFs::new("/project").file(
+        "app.yaml",
+        "hosts: [a]
+ports: [80, 443]
+",
+    );
+
+    let (cfg, _, _) = OptionalVecConfig::parse_info_with_fs(
+        ["app", "--hosts", "b", "c", "--ports", "8080", "--", "x.txt"],
+        &fs,
+    );
+    assert_eq!(cfg.hosts, Some(vec!["a".into(), "b".into(), "c".into()]));
+    assert_eq!(cfg.ports, Some(vec![8080]));
+    assert_eq!(cfg.files, Some(vec!["x.txt".into()]));
+
+    let (cfg, _, _) =
+        OptionalVecConfig::parse_info_with_fs(["app", "--hosts", "b"], &MemoryFs::new("/"));
+    assert_eq!(cfg.hosts, Some(vec!["b".into()]));
+    assert_eq!(cfg.to_args(), ["--hosts", "b"]);
+}
+
+#[test]
+fn optional_vec_from_env() {
+    std::env::set_var("CCF_TEST_OPT_VEC_HOSTS", "x,y");
+    let (cfg, _, _) = OptionalVecConfig::parse_info_with_fs(["app"], &MemoryFs::new("/"));
+    std::env::remove_var("CCF_TEST_OPT_VEC_HOSTS");
+    assert_eq!(cfg.hosts, Some(vec!["x".into(), "y".into()]));
+}
+
+mod parsers {
+    pub fn port(s: &str) -> Result<u16, String> {
+        match s.parse::<u16>() {
+            Ok(p) if p >= 1024 => Ok(p),
+            Ok(p) => Err(format!("port {} is privileged", p)),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct ValueParserConfig {
+    #[config_arg(value_parser = parsers::port)]
+    pub port: u16,
+
+    #[config_arg(value_parser = parsers::port)]
+    pub extra_ports: Vec<u16>,
+}
+
+#[test]
+fn value_parser_applies_to_cli_and_config() {
+    let fs = MemoryFs::new("/project").file("app.yaml", "port: 8080\nextra_ports: [2000, 3000]\n");
+
+    let (cfg, _, _) = ValueParserConfig::parse_info_with_fs(["app", "--extra-ports", "4000"], &fs);
+    assert_eq!(cfg.port, 8080);
+    assert_eq!(cfg.extra_ports, vec![2000, 3000, 4000]);
+
+    let err = ValueParserConfig::try_parse_info_with_fs(["app", "--port", "80"], &fs)
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("privileged"), "{}", err);
+}
+
+#[test]
+fn value_parser_rejects_bad_config_elements() {
+    let fs = MemoryFs::new("/project").file("app.yaml", "port: 8080\nextra_ports: [2000, 22]\n");
+
+    let err = ValueParserConfig::try_parse_info_with_fs(["app"], &fs)
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("port 22 is privileged"), "{}", err);
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Server {
+    pub name: String,
+    pub port: u16,
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "app"]
+struct ServersConfig {
+    #[config_arg(
+        accept_from = "config_only",
+        multi_value_behavior = "merge_by_key",
+        key = "name"
+    )]
+    pub servers: Vec<Server>,
+
+    #[config_arg(
+        accept_from = "config_only",
+        multi_value_behavior = "merge_by_key",
+        key = "name"
+    )]
+    pub backup_servers: Vec<Server>,
+}
+
+#[test]
+fn merge_by_key_upserts_list_entries_across_sources() {
+    let fs = MemoryFs::new("/project")
+        .file(
+            "base.yaml",
+            "servers:\n  - { name: a, port: 1 }\n  - { name: b, port: 2 }\n",
+        )
+        .file(
+            "local.yaml",
+            "servers:\n  - { name: b, port: 20 }\n  - { name: c, port: 3 }\n",
+        );
+
+    let (cfg, _, _) = ServersConfig::parse_info_with_fs(
+        [
+            "app",
+            "--config-file",
+            "base.yaml",
+            "--config-file",
+            "local.yaml",
+        ],
+        &fs,
+    );
+
+    let server = |name: &str, port| Server {
+        name: name.into(),
+        port,
+    };
+    assert_eq!(
+        cfg.servers,
+        [server("a", 1), server("b", 20), server("c", 3)]
+    );
+}
+
+#[test]
+fn merge_by_key_matches_either_key_spelling() {
+    let fs = MemoryFs::new("/project")
+        .file(
+            "base.yaml",
+            "backup_servers:\n  - { name: a, port: 1 }\n  - { name: b, port: 2 }\n",
+        )
+        .file("local.yaml", "backup-servers:\n  - { name: b, port: 20 }\n");
+
+    let (cfg, _, _) = ServersConfig::parse_info_with_fs(
+        [
+            "app",
+            "--config-file",
+            "base.yaml",
+            "--config-file",
+            "local.yaml",
+        ],
+        &fs,
+    );
+
+    let server = |name: &str, port| Server {
+        name: name.into(),
+        port,
+    };
+    assert_eq!(cfg.backup_servers, [server("a", 1), server("b", 20)]);
+}
+
+#[derive(ClapConfigFile)]
+#[config_file_name = "plugin"]
+// Ignored: `rest` takes the unknown keys
+#[config_deny_unknown]
+struct PluginConfig {
+    #[config_arg()]
+    pub name: String,
+
+    #[config_arg(rest)]
+    pub extra: std::collections::HashMap<String, serde_json::Value>,
+}
+
+#[test]
+fn rest_collects_unknown_keys_and_serializes_them_back() {
+    let fs = MemoryFs::new("/project")
+        .file("plugin.yaml", "name: lint\nlevel: 3\nrules:\n  - no-todo\n");
+
+    let (cfg, _, _) = PluginConfig::parse_info_with_fs(["plugin", "--name", "fmt"], &fs);
+
+    assert_eq!(cfg.name, "fmt");
+    assert_eq!(cfg.extra.len(), 2);
+    assert_eq!(cfg.extra["level"], 3);
+    assert_eq!(cfg.extra["rules"], serde_json::json!(["no-todo"]));
+    assert_eq!(
+        serde_json::to_value(&cfg).unwrap(),
+        serde_json::json!({"name": "fmt", "level": 3, "rules": ["no-todo"]})
+    );
+}
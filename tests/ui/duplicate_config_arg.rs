@@ -0,0 +1,10 @@
+use clap_config_file::ClapConfigFile;
+
+#[derive(ClapConfigFile)]
+struct Config {
+    #[config_arg(short = 'p')]
+    #[config_arg(default_value = "8080")]
+    pub port: u16,
+}
+
+fn main() {}
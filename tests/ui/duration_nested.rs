@@ -0,0 +1,10 @@
+use clap_config_file::ClapConfigFile;
+use std::time::Duration;
+
+#[derive(ClapConfigFile)]
+struct Config {
+    #[config_arg()]
+    pub timeouts: Option<Vec<Duration>>,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use clap_config_file::ClapConfigFile;
+
+#[derive(ClapConfigFile)]
+#[config_getters]
+struct Config {
+    #[config_arg()]
+    pub command: String,
+}
+
+fn main() {}
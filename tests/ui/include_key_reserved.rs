@@ -0,0 +1,10 @@
+use clap_config_file::ClapConfigFile;
+
+#[derive(ClapConfigFile)]
+#[config_file_include]
+struct Config {
+    #[config_arg(multi_value_behavior = "extend")]
+    pub include: Vec<String>,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use clap_config_file::ClapConfigFile;
+
+#[derive(ClapConfigFile)]
+struct Config {
+    #[config_arg(default_value = "80a")]
+    pub port: u16,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use clap_config_file::ClapConfigFile;
+use std::collections::HashMap;
+
+#[derive(ClapConfigFile)]
+struct Config {
+    #[config_arg(num_args = 3)]
+    pub labels: HashMap<String, String>,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use clap_config_file::ClapConfigFile;
+
+#[derive(ClapConfigFile)]
+struct Config {
+    #[config_arg(rest)]
+    pub extra: Vec<String>,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use clap_config_file::ClapConfigFile;
+
+#[derive(ClapConfigFile)]
+struct Config {
+    #[config_arg(conflicts_with = "json")]
+    pub quiet: bool,
+
+    #[config_arg()]
+    pub json_output: bool,
+}
+
+fn main() {}